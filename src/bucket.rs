@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
 use reqwest::Method;
 use serde::{de::DeserializeOwned, Deserialize};
 use serde_xml_rs::from_str;
@@ -8,11 +9,39 @@ use url::Url;
 
 use crate::{
     client::Client,
-    error::OssError,
+    error::{check_response, OssError},
     object::{Object, Objects},
     types::{CanonicalizedResource, EndPoint, ObjectQuery, StorageClass},
 };
 
+mod delete;
+mod post_policy;
+pub use delete::{DeleteError, DeleteResult, MAX_DELETE_OBJECTS};
+pub use post_policy::{PostForm, PostPolicy};
+
+pub(crate) fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub(crate) fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Bucket {
     name: String,
@@ -63,6 +92,32 @@ impl Bucket {
         Url::parse(&url).unwrap()
     }
 
+    /// 与 `to_url` 相同，bucket 名称或 endpoint 无效时返回错误而不是 panic
+    /// ```
+    /// # use aliyun_oss_client::{Bucket, EndPoint};
+    /// assert!(Bucket::new("foo-1", EndPoint::CN_QINGDAO).try_to_url().is_ok());
+    /// assert!(Bucket::new("Foo", EndPoint::CN_QINGDAO).try_to_url().is_err());
+    /// assert!(Bucket::new("-foo", EndPoint::CN_QINGDAO).try_to_url().is_err());
+    /// ```
+    pub fn try_to_url(&self) -> Result<Url, OssError> {
+        if !Self::is_valid_name(&self.name) {
+            return Err(OssError::InvalidBucket);
+        }
+        self.endpoint.try_to_url()?;
+
+        Ok(self.to_url())
+    }
+
+    /// bucket 名称只能包含小写字母、数字和短横线，以小写字母或数字开头和结尾，长度为 3-63
+    pub(crate) fn is_valid_name(name: &str) -> bool {
+        (3..=63).contains(&name.len())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !name.starts_with('-')
+            && !name.ends_with('-')
+    }
+
     /// 调用 api 导出 bucket 详情信息到自定义类型
     ///
     /// aliyun api 返回的 xml 是如下格式：
@@ -106,7 +161,6 @@ impl Bucket {
     ///     Name: String,
     /// }
     /// ```
-
     pub async fn export_info<B: DeserializeOwned>(&self, client: &Client) -> Result<B, OssError> {
         const BUCKET_INFO: &str = "bucketInfo";
 
@@ -115,15 +169,8 @@ impl Bucket {
         let method = Method::GET;
         let resource = CanonicalizedResource::from_bucket_info(self);
 
-        let header_map = client.authorization(method, resource)?;
-
-        let content = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let response = client.request(method, url, resource).send().await?;
+        let content = check_response(response).await?.text().await?;
 
         //println!("{}", content);
 
@@ -145,15 +192,8 @@ impl Bucket {
         let method = Method::GET;
         let resource = CanonicalizedResource::from_bucket_info(self);
 
-        let header_map = client.authorization(method, resource)?;
-
-        let content = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let response = client.request(method, url, resource).send().await?;
+        let content = check_response(response).await?.text().await?;
 
         //println!("{content}");
         Self::parse_info_xml(content)
@@ -205,6 +245,26 @@ impl Bucket {
         }
     }
 
+    /// 按顺序返回所有 `<field>...</field>` 之间的内容
+    pub(crate) fn parse_blocks<'a>(xml: &'a str, field: &str) -> Vec<&'a str> {
+        let start_tag = format!("<{}>", field);
+        let end_tag = format!("</{}>", field);
+
+        let mut blocks = vec![];
+        let mut rest = xml;
+        while let Some(start) = rest.find(&start_tag) {
+            rest = &rest[start + start_tag.len()..];
+            match rest.find(&end_tag) {
+                Some(end) => {
+                    blocks.push(&rest[..end]);
+                    rest = &rest[end + end_tag.len()..];
+                }
+                None => break,
+            }
+        }
+        blocks
+    }
+
     /// 调用 aliyun api 返回 object 列表到自定义类型，它还会返回用于翻页的 `NextContinuationToken`
     ///
     /// aliyun api 返回的 xml 是如下格式：
@@ -236,21 +296,14 @@ impl Bucket {
         let method = Method::GET;
         let resource = CanonicalizedResource::from_object_list(self, query.get_next_token());
 
-        let header_map = client.authorization(method, resource)?;
-
-        let content = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let response = client.request(method, url, resource).send().await?;
+        let content = check_response(response).await?.text().await?;
 
         //println!("{content}");
 
         #[derive(Debug, Deserialize)]
         struct ListBucketResult<T> {
-            #[serde(rename = "Contents")]
+            #[serde(rename = "Contents", default = "Vec::new")]
             contents: Vec<T>,
             #[serde(rename = "NextContinuationToken")]
             next_token: Option<String>,
@@ -270,49 +323,201 @@ impl Bucket {
         let method = Method::GET;
         let resource = CanonicalizedResource::from_object_list(self, query.get_next_token());
 
-        let header_map = client.authorization(method, resource)?;
-
-        let content = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let response = client.request(method, url, resource).send().await?;
+        let content = check_response(response).await?.text().await?;
 
         //println!("{content}");
 
         let list = Self::parse_xml_objects(&content)?;
+        let common_prefixes = Self::parse_common_prefixes(&content);
 
         let token = Self::parse_item(&content, "NextContinuationToken").map(|t| t.to_owned());
 
-        Ok(Objects::new(list, token))
+        Ok(Objects::new(list, token)
+            .with_bucket(self.clone())
+            .with_common_prefixes(common_prefixes))
     }
 
-    pub(crate) fn parse_xml_objects(xml: &str) -> Result<Vec<Object>, OssError> {
-        let mut start_positions = vec![];
-        let mut end_positions = vec![];
-        let mut start = 0;
-        let mut pattern = "<Key>";
-
-        while let Some(pos) = xml[start..].find(pattern) {
-            start_positions.push(start + pos);
-            start += pos + pattern.len();
-        }
-        start = 0;
-        pattern = "</Key>";
-        while let Some(pos) = xml[start..].find(pattern) {
-            end_positions.push(start + pos);
-            start += pos + pattern.len();
+    /// # 以异步流的形式返回 object 列表
+    ///
+    /// 会按需自动翻页，`limit` 可以限制返回的 object 总数，达到数量后不再请求下一页
+    ///
+    /// ```no_run
+    /// # use aliyun_oss_client::{Bucket, Client, EndPoint, types::ObjectQuery};
+    /// use futures_util::TryStreamExt;
+    /// # async fn run(client: &Client) -> Result<(), aliyun_oss_client::error::OssError> {
+    /// let bucket = Bucket::new("foo", EndPoint::CN_QINGDAO);
+    /// let mut query = ObjectQuery::new();
+    /// query.insert(ObjectQuery::PREFIX, "logs/");
+    ///
+    /// let mut stream = Box::pin(bucket.list_objects_stream(&query, Some(5000), client));
+    /// while let Some(object) = stream.try_next().await? {
+    ///     println!("{}", object.get_path());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_objects_stream<'a>(
+        &'a self,
+        query: &ObjectQuery,
+        limit: Option<usize>,
+        client: &'a Client,
+    ) -> impl Stream<Item = Result<Object, OssError>> + 'a {
+        let query = query.clone();
+
+        stream::try_unfold(Some(query), move |query| async move {
+            let mut query = match query {
+                Some(query) => query,
+                None => return Ok(None),
+            };
+            let list = self.get_objects(&query, client).await?;
+            let next = list.next_token().map(|token| {
+                query.insert_next_token(token.to_owned());
+                query
+            });
+            Ok::<_, OssError>(Some((list.into_vec(), next)))
+        })
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+        .take(limit.unwrap_or(usize::MAX))
+    }
+
+    /// 列出 `prefix` 前缀下的全部文件，会自动翻页
+    async fn list_prefix(&self, prefix: &str, client: &Client) -> Result<Vec<Object>, OssError> {
+        let mut query = ObjectQuery::new();
+        query.insert(ObjectQuery::PREFIX, prefix);
+        query.insert(ObjectQuery::MAX_KEYS, "1000");
+
+        self.list_objects_stream(&query, None, client)
+            .try_collect()
+            .await
+    }
+
+    /// 列出 `prefix` 前缀下的全部文件路径，会自动翻页
+    pub(crate) async fn list_keys(
+        &self,
+        prefix: &str,
+        client: &Client,
+    ) -> Result<Vec<String>, OssError> {
+        let list = self.list_prefix(prefix, client).await?;
+        Ok(list.iter().map(|o| o.get_path().to_owned()).collect())
+    }
+
+    /// # 删除目录
+    ///
+    /// 列出 `prefix` 前缀下的全部文件，并使用批量删除接口逐组删除。
+    /// `prefix` 为空字符串时返回 [`OssError::EmptyPrefix`]，删除所有文件需要使用 [`Bucket::delete_all`]
+    pub async fn delete_prefix(
+        &self,
+        prefix: &str,
+        client: &Client,
+    ) -> Result<DeleteResult, OssError> {
+        let keys = self.delete_prefix_dry_run(prefix, client).await?;
+        self.delete_objects(keys, false, client).await
+    }
+
+    /// 与 [`Bucket::delete_prefix`] 相同，但不会删除文件，只返回将要被删除的文件路径
+    pub async fn delete_prefix_dry_run(
+        &self,
+        prefix: &str,
+        client: &Client,
+    ) -> Result<Vec<String>, OssError> {
+        if prefix.is_empty() {
+            return Err(OssError::EmptyPrefix);
         }
+        self.list_keys(prefix, client).await
+    }
 
-        let mut list = vec![];
-        for i in 0..start_positions.len() {
-            let path = &xml[start_positions[i] + 5..end_positions[i]];
-            list.push(Object::new(path.to_owned()))
+    /// # 清空 bucket
+    ///
+    /// 删除 bucket 中的所有文件，不可恢复
+    pub async fn delete_all(&self, client: &Client) -> Result<DeleteResult, OssError> {
+        let keys = self.list_keys("", client).await?;
+        self.delete_objects(keys, false, client).await
+    }
+
+    /// # 重命名目录
+    ///
+    /// 先列出 `from` 前缀下的全部文件，再逐个在服务端复制到 `to` 前缀下并删除原文件。
+    /// 两个前缀相同或者互相包含（例如 `a/` 与 `a/b/`）时返回 [`OssError::InvalidRenameTarget`]
+    ///
+    /// ```no_run
+    /// # use aliyun_oss_client::{Bucket, Client, EndPoint};
+    /// # async fn run(client: &Client) -> Result<(), aliyun_oss_client::error::OssError> {
+    /// let bucket = Bucket::new("foo", EndPoint::CN_QINGDAO);
+    /// let report = bucket.rename_prefix("logs/2022/", "archive/2022/", client).await?;
+    /// for (key, err) in report.failed() {
+    ///     println!("{key}: {err:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rename_prefix(
+        &self,
+        from: &str,
+        to: &str,
+        client: &Client,
+    ) -> Result<RenameReport, OssError> {
+        if from.starts_with(to) || to.starts_with(from) {
+            return Err(OssError::InvalidRenameTarget);
         }
+        let list = self.list_prefix(from, client).await?;
+
+        let mut client = client.clone();
+        client.set_bucket(self.clone());
+
+        let mut report = RenameReport::default();
+        for object in list {
+            let key = object.get_path().to_owned();
+            let target = Object::new(format!("{}{}", to, key.strip_prefix(from).unwrap_or(&key)));
+            match object.rename_to(&target, &client).await {
+                Ok(()) => report.renamed.push(key),
+                Err(e) => report.failed.push((key, e)),
+            }
+        }
+
+        Ok(report)
+    }
+
+    pub(crate) fn parse_xml_objects(xml: &str) -> Result<Vec<Object>, OssError> {
+        Self::parse_blocks(xml, "Contents")
+            .into_iter()
+            .map(Object::from_list_xml)
+            .collect()
+    }
 
-        Ok(list)
+    /// 解析使用 `delimiter` 查询时返回的 `<CommonPrefixes>`，即当前层级下的子目录
+    pub(crate) fn parse_common_prefixes(xml: &str) -> Vec<String> {
+        Self::parse_blocks(xml, "CommonPrefixes")
+            .into_iter()
+            .filter_map(|block| Self::parse_item(block, "Prefix"))
+            .map(xml_unescape)
+            .collect()
+    }
+}
+
+/// # 批量重命名的结果
+///
+/// 单个文件失败不会中断整个操作，失败的文件及原因会记录在 `failed` 中
+#[derive(Debug, Default)]
+pub struct RenameReport {
+    renamed: Vec<String>,
+    failed: Vec<(String, OssError)>,
+}
+
+impl RenameReport {
+    /// 重命名成功的文件（原路径）
+    pub fn renamed(&self) -> &[String] {
+        &self.renamed
+    }
+
+    /// 重命名失败的文件（原路径）以及对应的错误
+    pub fn failed(&self) -> &[(String, OssError)] {
+        &self.failed
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
     }
 }
 
@@ -381,11 +586,185 @@ mod tests {
 
     use crate::{
         client::init_client,
-        types::{EndPoint, ObjectQuery},
+        error::OssError,
+        test_server::{self, serve},
+        types::{EndPoint, ObjectQuery, StorageClass},
     };
 
     use super::Bucket;
 
+    #[test]
+    fn test_parse_list_with_delimiter() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <ListBucketResult>
+            <Name>foo</Name>
+            <Prefix>fun/</Prefix>
+            <MaxKeys>100</MaxKeys>
+            <Delimiter>/</Delimiter>
+            <IsTruncated>false</IsTruncated>
+            <Contents>
+                <Key>fun/a&amp;b.jpg</Key>
+                <LastModified>2022-06-26T09:53:21.000Z</LastModified>
+                <ETag>"F75A15996D0857B16FA31A3B16624C26"</ETag>
+                <Type>Normal</Type>
+                <Size>18027</Size>
+                <StorageClass>Standard</StorageClass>
+            </Contents>
+            <CommonPrefixes>
+                <Prefix>fun/movie/</Prefix>
+            </CommonPrefixes>
+            <CommonPrefixes>
+                <Prefix>fun/music/</Prefix>
+            </CommonPrefixes>
+        </ListBucketResult>"#;
+
+        let list = Bucket::parse_xml_objects(xml).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].get_path(), "fun/a&b.jpg");
+        assert_eq!(list[0].size(), Some(18027));
+        assert_eq!(list[0].storage_class(), Some(&StorageClass::STANDARD));
+        assert!(list[0].owner().is_none());
+
+        let prefixes = Bucket::parse_common_prefixes(xml);
+        assert_eq!(prefixes, vec!["fun/movie/", "fun/music/"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_objects_stream_paging() {
+        use futures_util::TryStreamExt;
+
+        let (server, handle) = serve(vec![
+            (
+                200,
+                r#"<ListBucketResult>
+                    <IsTruncated>true</IsTruncated>
+                    <NextContinuationToken>token-2</NextContinuationToken>
+                    <Contents><Key>a/1.txt</Key></Contents>
+                    <Contents><Key>a/2.txt</Key></Contents>
+                </ListBucketResult>"#
+                    .to_owned(),
+            ),
+            (
+                200,
+                r#"<ListBucketResult>
+                    <IsTruncated>false</IsTruncated>
+                    <Contents><Key>a/3.txt</Key></Contents>
+                </ListBucketResult>"#
+                    .to_owned(),
+            ),
+        ]);
+        let client = test_server::client(&server);
+
+        let mut query = ObjectQuery::new();
+        query.insert(ObjectQuery::PREFIX, "a/");
+        let list: Vec<_> = client
+            .bucket()
+            .unwrap()
+            .list_objects_stream(&query, None, &client)
+            .try_collect()
+            .await
+            .unwrap();
+        let keys: Vec<_> = list.iter().map(|o| o.get_path()).collect();
+        assert_eq!(keys, ["a/1.txt", "a/2.txt", "a/3.txt"]);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].line.contains("continuation-token"));
+        assert!(requests[1].line.contains("continuation-token=token-2"));
+        assert!(requests[1].line.contains("prefix=a%2F"));
+
+        // 达到 limit 后不再请求下一页
+        let (server, handle) = serve(vec![(
+            200,
+            r#"<ListBucketResult>
+                <NextContinuationToken>token-2</NextContinuationToken>
+                <Contents><Key>a/1.txt</Key></Contents>
+                <Contents><Key>a/2.txt</Key></Contents>
+            </ListBucketResult>"#
+                .to_owned(),
+        )]);
+        let client = test_server::client(&server);
+        let list: Vec<_> = client
+            .bucket()
+            .unwrap()
+            .list_objects_stream(&query, Some(2), &client)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(handle.join().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_empty_prefix() {
+        let client = test_server::client("http://127.0.0.1:1");
+        let bucket = client.bucket().unwrap();
+
+        let err = bucket.delete_prefix("", &client).await.unwrap_err();
+        assert!(matches!(err, OssError::EmptyPrefix));
+        let err = bucket.delete_prefix_dry_run("", &client).await.unwrap_err();
+        assert!(matches!(err, OssError::EmptyPrefix));
+    }
+
+    #[tokio::test]
+    async fn test_rename_prefix_overlap() {
+        let client = test_server::client("http://127.0.0.1:1");
+        let bucket = client.bucket().unwrap();
+
+        for (from, to) in [("a/", "a/"), ("a/", "a/b/"), ("a/b/", "a/"), ("", "a/")] {
+            let err = bucket.rename_prefix(from, to, &client).await.unwrap_err();
+            assert!(
+                matches!(err, OssError::InvalidRenameTarget),
+                "{from} -> {to}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_prefix_report() {
+        let list = r#"<ListBucketResult>
+            <Contents><Key>a/1.txt</Key><Size>1</Size></Contents>
+            <Contents><Key>a/2.txt</Key><Size>1</Size></Contents>
+            <Contents><Key>a/3.txt</Key><Size>1</Size></Contents>
+        </ListBucketResult>"#;
+        let denied = "<Error><Code>AccessDenied</Code></Error>".to_owned();
+        let (server, handle) = serve(vec![
+            (200, list.to_owned()),
+            // a/1.txt 复制并删除成功
+            (200, String::new()),
+            (204, String::new()),
+            // a/2.txt 复制失败，不会删除
+            (403, denied.clone()),
+            // a/3.txt 复制成功但删除失败
+            (200, String::new()),
+            (403, denied),
+        ]);
+        let client = test_server::client(&server);
+
+        let report = client
+            .bucket()
+            .unwrap()
+            .rename_prefix("a/", "b/", &client)
+            .await
+            .unwrap();
+        assert!(!report.is_success());
+        assert_eq!(report.renamed(), ["a/1.txt"]);
+        let failed: Vec<_> = report.failed().iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(failed, ["a/2.txt", "a/3.txt"]);
+
+        let lines: Vec<_> = handle.join().unwrap().into_iter().map(|r| r.line).collect();
+        assert_eq!(
+            lines[1..],
+            [
+                "PUT /b/1.txt HTTP/1.1",
+                "DELETE /a/1.txt HTTP/1.1",
+                "PUT /b/2.txt HTTP/1.1",
+                "PUT /b/3.txt HTTP/1.1",
+                "DELETE /a/3.txt HTTP/1.1",
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_get_info() {
         let bucket = Bucket::new("honglei123", EndPoint::CN_SHANGHAI);
@@ -0,0 +1,250 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use md5::{Digest, Md5};
+use reqwest::{header::HeaderMap, Method};
+
+use crate::{
+    bucket::{xml_escape, xml_unescape, Bucket},
+    client::{Client, CONTENT_MD5},
+    error::{check_response, OssError},
+    types::CanonicalizedResource,
+};
+
+/// 单次 DeleteMultipleObjects 请求最多可以删除的文件数量
+pub const MAX_DELETE_OBJECTS: usize = 1000;
+
+/// # 批量删除的结果
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeleteResult {
+    deleted: Vec<String>,
+    errors: Vec<DeleteError>,
+}
+
+/// 删除失败的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteError {
+    key: String,
+    code: String,
+    message: String,
+}
+
+impl DeleteError {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl DeleteResult {
+    /// 删除成功的文件，quiet 模式下 OSS 不返回该列表
+    pub fn deleted(&self) -> &[String] {
+        &self.deleted
+    }
+
+    /// 删除失败的文件
+    pub fn errors(&self) -> &[DeleteError] {
+        &self.errors
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    fn extend(&mut self, other: DeleteResult) {
+        self.deleted.extend(other.deleted);
+        self.errors.extend(other.errors);
+    }
+
+    fn parse_xml(xml: &str) -> DeleteResult {
+        let deleted = Bucket::parse_blocks(xml, "Deleted")
+            .into_iter()
+            .filter_map(|block| Bucket::parse_item(block, "Key"))
+            .map(xml_unescape)
+            .collect();
+
+        let errors = Bucket::parse_blocks(xml, "Error")
+            .into_iter()
+            .map(|block| DeleteError {
+                key: xml_unescape(Bucket::parse_item(block, "Key").unwrap_or_default()),
+                code: Bucket::parse_item(block, "Code")
+                    .unwrap_or_default()
+                    .to_owned(),
+                message: xml_unescape(Bucket::parse_item(block, "Message").unwrap_or_default()),
+            })
+            .collect();
+
+        DeleteResult { deleted, errors }
+    }
+}
+
+fn delete_body(keys: &[String], quiet: bool) -> String {
+    let mut body = String::from(r#"<?xml version="1.0" encoding="UTF-8"?><Delete><Quiet>"#);
+    body += if quiet { "true" } else { "false" };
+    body += "</Quiet>";
+    for key in keys {
+        body += "<Object><Key>";
+        body += &xml_escape(key);
+        body += "</Key></Object>";
+    }
+    body += "</Delete>";
+    body
+}
+
+impl Bucket {
+    /// # 批量删除文件（DeleteMultipleObjects）
+    ///
+    /// 文件会按每组 1000 个分批提交，`quiet` 为 true 时 OSS 只返回删除失败的文件
+    ///
+    /// ```no_run
+    /// # use aliyun_oss_client::{Bucket, Client, EndPoint};
+    /// # async fn run(client: &Client) -> Result<(), aliyun_oss_client::error::OssError> {
+    /// let bucket = Bucket::new("foo", EndPoint::CN_QINGDAO);
+    /// let result = bucket.delete_objects(["tmp/a.txt", "tmp/b.txt"], false, client).await?;
+    /// assert!(result.is_success());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_objects<I, K>(
+        &self,
+        keys: I,
+        quiet: bool,
+        client: &Client,
+    ) -> Result<DeleteResult, OssError>
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        let keys: Vec<String> = keys.into_iter().map(Into::into).collect();
+
+        let mut result = DeleteResult::default();
+        for chunk in keys.chunks(MAX_DELETE_OBJECTS) {
+            result.extend(self.delete_objects_once(chunk, quiet, client).await?);
+        }
+        Ok(result)
+    }
+
+    async fn delete_objects_once(
+        &self,
+        keys: &[String],
+        quiet: bool,
+        client: &Client,
+    ) -> Result<DeleteResult, OssError> {
+        let mut url = self.to_url();
+        url.set_query(Some("delete"));
+        let method = Method::POST;
+        let resource = CanonicalizedResource::new(format!("/{}/?delete", self.as_str()));
+
+        let body = delete_body(keys, quiet);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_MD5,
+            STANDARD.encode(Md5::digest(body.as_bytes())).try_into()?,
+        );
+
+        let response = client
+            .request(method, url, resource)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await?;
+
+        let response = check_response(response).await?;
+        let content = response.text().await?;
+
+        Ok(DeleteResult::parse_xml(&content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use md5::{Digest, Md5};
+
+    use super::{delete_body, DeleteResult, MAX_DELETE_OBJECTS};
+    use crate::{
+        test_server::{self, serve},
+        types::Secret,
+    };
+
+    #[tokio::test]
+    async fn test_delete_objects_batches() {
+        let keys: Vec<String> = (0..=MAX_DELETE_OBJECTS)
+            .map(|i| format!("tmp/{}.txt", i))
+            .collect();
+        let (server, handle) = serve(vec![
+            (
+                200,
+                "<DeleteResult><Deleted><Key>tmp/0.txt</Key></Deleted></DeleteResult>".to_owned(),
+            ),
+            (
+                200,
+                "<DeleteResult><Deleted><Key>tmp/1000.txt</Key></Deleted></DeleteResult>"
+                    .to_owned(),
+            ),
+        ]);
+        let client = test_server::client(&server);
+
+        let result = client
+            .bucket()
+            .unwrap()
+            .delete_objects(keys, false, &client)
+            .await
+            .unwrap();
+        assert_eq!(result.deleted(), ["tmp/0.txt", "tmp/1000.txt"]);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        for (request, count) in requests.iter().zip([MAX_DELETE_OBJECTS, 1]) {
+            assert_eq!(request.line, "POST /?delete HTTP/1.1");
+            let body = String::from_utf8_lossy(&request.body);
+            assert_eq!(body.matches("<Object>").count(), count);
+
+            // Content-MD5 是请求体的摘要，并且参与了 V1 签名
+            let md5 = STANDARD.encode(Md5::digest(&request.body));
+            assert_eq!(request.header("content-md5"), Some(md5.as_str()));
+            let string = format!(
+                "POST\n{}\n\n{}\n/bucket/?delete",
+                md5,
+                request.header("date").unwrap()
+            );
+            let sign = format!(
+                "OSS foo:{}",
+                Secret::new("bar").encryption(string.as_bytes()).unwrap()
+            );
+            assert_eq!(request.header("authorization"), Some(sign.as_str()));
+        }
+        assert!(String::from_utf8_lossy(&requests[1].body).contains("<Key>tmp/1000.txt</Key>"));
+    }
+
+    #[test]
+    fn test_delete_body() {
+        let body = delete_body(&["a.txt".to_owned(), "b&c.txt".to_owned()], true);
+        assert_eq!(
+            body,
+            r#"<?xml version="1.0" encoding="UTF-8"?><Delete><Quiet>true</Quiet><Object><Key>a.txt</Key></Object><Object><Key>b&amp;c.txt</Key></Object></Delete>"#
+        );
+    }
+
+    #[test]
+    fn test_parse_result() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <DeleteResult>
+            <Deleted><Key>a.txt</Key></Deleted>
+            <Deleted><Key>b&amp;c.txt</Key></Deleted>
+            <Error><Key>d.txt</Key><Code>AccessDenied</Code><Message>Access denied.</Message></Error>
+        </DeleteResult>"#;
+
+        let result = DeleteResult::parse_xml(xml);
+        assert_eq!(result.deleted(), ["a.txt", "b&c.txt"]);
+        assert_eq!(result.errors().len(), 1);
+        assert_eq!(result.errors()[0].key(), "d.txt");
+        assert_eq!(result.errors()[0].code(), "AccessDenied");
+        assert!(!result.is_success());
+    }
+}
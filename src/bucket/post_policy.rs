@@ -0,0 +1,305 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::{bucket::Bucket, client::Client, credentials::SECURITY_TOKEN, error::OssError};
+
+/// # PostObject 表单上传的策略
+///
+/// 限制表单上传的有效期以及允许的 bucket、文件名、大小和类型，
+/// 通过 [`Bucket::post_form`] 签名后生成表单字段
+///
+/// ```
+/// # use chrono::{Duration, Utc};
+/// # use aliyun_oss_client::bucket::PostPolicy;
+/// let policy = PostPolicy::new(Utc::now() + Duration::minutes(30))
+///     .bucket("my-bucket")
+///     .key_starts_with("user/1/")
+///     .content_length_range(1, 10 * 1024 * 1024)
+///     .content_type("image/png");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PostPolicy {
+    expiration: DateTime<Utc>,
+    conditions: Vec<Value>,
+    content_type: Option<String>,
+}
+
+impl PostPolicy {
+    /// 策略的过期时间，过期后表单无法再上传
+    pub fn new(expiration: DateTime<Utc>) -> Self {
+        PostPolicy {
+            expiration,
+            conditions: Vec::new(),
+            content_type: None,
+        }
+    }
+
+    /// 只能上传到指定的 bucket
+    pub fn bucket<N: Into<String>>(mut self, name: N) -> Self {
+        self.conditions.push(json!({ "bucket": name.into() }));
+        self
+    }
+
+    /// 文件名必须与 `key` 相同
+    pub fn key<K: Into<String>>(mut self, key: K) -> Self {
+        self.conditions.push(json!(["eq", "$key", key.into()]));
+        self
+    }
+
+    /// 文件名必须以 `prefix` 开头
+    pub fn key_starts_with<P: Into<String>>(mut self, prefix: P) -> Self {
+        self.conditions
+            .push(json!(["starts-with", "$key", prefix.into()]));
+        self
+    }
+
+    /// 文件大小的范围，单位为字节，包含 `min` 和 `max`
+    pub fn content_length_range(mut self, min: u64, max: u64) -> Self {
+        self.conditions
+            .push(json!(["content-length-range", min, max]));
+        self
+    }
+
+    /// `Content-Type` 必须与 `content_type` 相同，生成的表单中也会带上该字段
+    pub fn content_type<T: Into<String>>(mut self, content_type: T) -> Self {
+        let content_type = content_type.into();
+        self.conditions
+            .push(json!(["eq", "$Content-Type", content_type]));
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// `Content-Type` 必须以 `prefix` 开头，例如 `image/`
+    pub fn content_type_starts_with<P: Into<String>>(mut self, prefix: P) -> Self {
+        self.conditions
+            .push(json!(["starts-with", "$Content-Type", prefix.into()]));
+        self
+    }
+
+    /// 策略的过期时间
+    pub fn expiration(&self) -> &DateTime<Utc> {
+        &self.expiration
+    }
+
+    /// 文件名是否满足策略中全部 `$key` 的条件
+    fn allows_key(&self, key: &str) -> bool {
+        self.conditions.iter().all(
+            |condition| match condition.as_array().map(|c| c.as_slice()) {
+                Some([op, name, value]) if name == "$key" => {
+                    let value = value.as_str().unwrap_or_default();
+                    match op.as_str() {
+                        Some("eq") => key == value,
+                        Some("starts-with") => key.starts_with(value),
+                        _ => true,
+                    }
+                }
+                _ => true,
+            },
+        )
+    }
+
+    /// 策略的 json 文本
+    /// ```
+    /// # use chrono::{TimeZone, Utc};
+    /// # use aliyun_oss_client::bucket::PostPolicy;
+    /// let policy = PostPolicy::new(Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap())
+    ///     .bucket("foo")
+    ///     .content_length_range(0, 1024);
+    /// assert_eq!(
+    ///     policy.to_json(),
+    ///     r#"{"expiration":"2024-01-02T03:04:05.000Z","conditions":[{"bucket":"foo"},["content-length-range",0,1024]]}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let expiration = self.expiration.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        let mut json = String::from("{\"expiration\":");
+        json += &Value::String(expiration).to_string();
+        json += ",\"conditions\":";
+        json += &Value::Array(self.conditions.clone()).to_string();
+        json += "}";
+        json
+    }
+
+    /// base64 编码后的策略，即表单中的 `policy` 字段
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.to_json())
+    }
+}
+
+/// # PostObject 表单
+///
+/// 向 [`url`](PostForm::url) 发送 `multipart/form-data` 请求，
+/// 依次带上 [`fields`](PostForm::fields) 中的字段，最后是名为 `file` 的文件内容
+#[derive(Debug, Clone)]
+pub struct PostForm {
+    url: Url,
+    fields: Vec<(String, String)>,
+}
+
+impl PostForm {
+    /// 表单提交的地址，即 bucket 的地址
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// 除文件以外的全部表单字段
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// 按名称查找表单字段的值
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl Bucket {
+    /// # 生成 PostObject 表单
+    ///
+    /// 使用 client 当前的凭证对策略签名，上传后的文件名为 `key`，
+    /// 使用 STS 临时凭证时表单中会带上 `x-oss-security-token`。
+    ///
+    /// `key` 不满足策略中 [`key`](PostPolicy::key) 或 [`key_starts_with`](PostPolicy::key_starts_with)
+    /// 的条件时返回 [`OssError::InvalidPostKey`]
+    ///
+    /// ```
+    /// # use chrono::{Duration, Utc};
+    /// # use aliyun_oss_client::{Bucket, Client, EndPoint, Key, Secret};
+    /// # use aliyun_oss_client::bucket::PostPolicy;
+    /// # async fn run() -> Result<(), aliyun_oss_client::error::OssError> {
+    /// let client = Client::new(Key::new("foo"), Secret::new("bar"));
+    /// let bucket = Bucket::new("my-bucket", EndPoint::CN_QINGDAO);
+    /// let policy = PostPolicy::new(Utc::now() + Duration::minutes(30))
+    ///     .bucket("my-bucket")
+    ///     .key_starts_with("user/1/");
+    ///
+    /// let form = bucket.post_form(&policy, "user/1/avatar.png", &client).await?;
+    /// assert_eq!(form.get("OSSAccessKeyId"), Some("foo"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn post_form(
+        &self,
+        policy: &PostPolicy,
+        key: &str,
+        client: &Client,
+    ) -> Result<PostForm, OssError> {
+        if !policy.allows_key(key) {
+            return Err(OssError::InvalidPostKey);
+        }
+        let credentials = client.credentials().await?;
+        let policy_base64 = policy.to_base64();
+        let signature = credentials
+            .secret()
+            .encryption(policy_base64.as_bytes())
+            .unwrap();
+
+        let mut fields = vec![
+            ("key".to_owned(), key.to_owned()),
+            (
+                "OSSAccessKeyId".to_owned(),
+                credentials.key().as_str().to_owned(),
+            ),
+            ("policy".to_owned(), policy_base64),
+            ("Signature".to_owned(), signature),
+        ];
+        if let Some(token) = credentials.security_token() {
+            fields.push((SECURITY_TOKEN.to_owned(), token.to_owned()));
+        }
+        if let Some(content_type) = &policy.content_type {
+            fields.push(("Content-Type".to_owned(), content_type.clone()));
+        }
+
+        Ok(PostForm {
+            url: self.try_to_url()?,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::PostPolicy;
+    use crate::{
+        credentials::Credentials,
+        error::OssError,
+        types::{EndPoint, Key, Secret},
+        Bucket, Client,
+    };
+
+    #[tokio::test]
+    async fn test_post_form() {
+        let credentials = Credentials::new(Key::new("STS.foo"), Secret::new("bar"))
+            .with_security_token("CAIS-token");
+        let client = Client::from_credentials(credentials);
+        let bucket = Bucket::new("my-bucket", EndPoint::CN_QINGDAO);
+
+        let policy = PostPolicy::new(Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap())
+            .bucket("my-bucket")
+            .key_starts_with("user/")
+            .content_type("image/png");
+        assert_eq!(
+            policy.to_json(),
+            r#"{"expiration":"2024-01-02T03:04:05.000Z","conditions":[{"bucket":"my-bucket"},["starts-with","$key","user/"],["eq","$Content-Type","image/png"]]}"#
+        );
+
+        let form = bucket
+            .post_form(&policy, "user/a.png", &client)
+            .await
+            .unwrap();
+        assert_eq!(
+            form.url().as_str(),
+            "https://my-bucket.oss-cn-qingdao.aliyuncs.com/"
+        );
+        assert_eq!(form.get("key"), Some("user/a.png"));
+        assert_eq!(form.get("OSSAccessKeyId"), Some("STS.foo"));
+        assert_eq!(form.get("policy"), Some(policy.to_base64().as_str()));
+        assert_eq!(
+            form.get("Signature").unwrap(),
+            Secret::new("bar")
+                .encryption(policy.to_base64().as_bytes())
+                .unwrap()
+        );
+        assert_eq!(form.get("x-oss-security-token"), Some("CAIS-token"));
+        assert_eq!(form.get("Content-Type"), Some("image/png"));
+    }
+
+    #[tokio::test]
+    async fn test_post_form_key() {
+        let client = Client::new(Key::new("foo"), Secret::new("bar"));
+        let bucket = Bucket::new("my-bucket", EndPoint::CN_QINGDAO);
+        let expiration = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let policy = PostPolicy::new(expiration).key_starts_with("user/");
+        assert!(bucket
+            .post_form(&policy, "user/a.png", &client)
+            .await
+            .is_ok());
+        let err = bucket
+            .post_form(&policy, "admin/a.png", &client)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OssError::InvalidPostKey));
+
+        let policy = PostPolicy::new(expiration).key("user/a.png");
+        assert!(bucket
+            .post_form(&policy, "user/a.png", &client)
+            .await
+            .is_ok());
+        assert!(bucket
+            .post_form(&policy, "user/b.png", &client)
+            .await
+            .is_err());
+
+        // 没有 `$key` 条件时不限制文件名
+        let policy = PostPolicy::new(expiration).content_length_range(0, 10);
+        assert!(bucket.post_form(&policy, "any.png", &client).await.is_ok());
+    }
+}
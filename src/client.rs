@@ -1,46 +1,125 @@
-use std::env::VarError;
+use std::{env::VarError, sync::Arc, time::Duration};
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use reqwest::{
     header::{HeaderMap, CONTENT_TYPE},
     Method,
 };
 use serde::{de::DeserializeOwned, Deserialize};
 use serde_xml_rs::from_str;
+use url::Url;
 
 use crate::{
     bucket::Bucket,
-    error::OssError,
-    types::{CanonicalizedResource, EndPoint, Key, Secret},
+    credentials::{
+        CachedProvider, Credentials, CredentialsProvider, StaticProvider, SECURITY_TOKEN,
+    },
+    error::{check_response, OssError},
+    types::{append_query, CanonicalizedResource, EndPoint, Key, Secret},
 };
 
-/// 存放 key, secret 以及默认 bucket 信息，几乎每个 api 都会用到它的引用
+mod builder;
+mod http;
+mod request;
+mod retry;
+mod v4;
+pub use builder::ClientBuilder;
+pub use http::{HttpClientBuilder, DEFAULT_USER_AGENT};
+pub(crate) use request::Request;
+pub use retry::RetryPolicy;
+
+pub(crate) const CONTENT_MD5: &str = "content-md5";
+/// V4 预签名 URL 的最长有效期
+const MAX_PRESIGN_EXPIRES_V4: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// 请求的签名版本
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignVersion {
+    /// `OSS AccessKeyId:Signature` 格式的 V1 签名
+    #[default]
+    V1,
+    /// `OSS4-HMAC-SHA256` 格式的 V4 签名，签名密钥与地域绑定
+    V4,
+}
+
+/// 存放访问凭证以及默认 bucket 信息，几乎每个 api 都会用到它的引用
 #[derive(Debug, Clone)]
 pub struct Client {
-    key: Key,
-    secret: Secret,
+    credentials: Arc<dyn CredentialsProvider>,
     bucket: Option<Bucket>,
+    retry: RetryPolicy,
+    http: reqwest::Client,
+    sign_version: SignVersion,
+    region: Option<String>,
+    additional_headers: Vec<String>,
+    /// 测试时把请求转发到本地的 HTTP 服务
+    #[cfg(test)]
+    test_server: Option<Url>,
 }
 
 impl Client {
     pub fn new(key: Key, secret: Secret) -> Client {
+        Self::from_credentials(Credentials::new(key, secret))
+    }
+
+    /// 使用 [`Credentials`] 创建 client，可以是带有 SecurityToken 的 STS 临时凭证
+    pub fn from_credentials(credentials: Credentials) -> Client {
+        Self::from_provider(StaticProvider::new(credentials))
+    }
+
+    /// 使用 [`CredentialsProvider`] 创建 client，获取的凭证会被缓存，临时凭证在过期前自动刷新
+    ///
+    /// ```
+    /// # use aliyun_oss_client::Client;
+    /// # use aliyun_oss_client::credentials::ChainProvider;
+    /// let client = Client::from_provider(ChainProvider::default_chain());
+    /// ```
+    pub fn from_provider<P: CredentialsProvider + 'static>(provider: P) -> Client {
+        Self::with_provider(Arc::new(CachedProvider::new(provider)))
+    }
+
+    fn with_provider(credentials: Arc<dyn CredentialsProvider>) -> Client {
         Self {
-            key,
-            secret,
+            credentials,
             bucket: None,
+            retry: RetryPolicy::default(),
+            http: default_http_client(),
+            sign_version: SignVersion::default(),
+            region: None,
+            additional_headers: Vec::new(),
+            #[cfg(test)]
+            test_server: None,
         }
     }
 
+    /// 通过 [`ClientBuilder`] 创建 client
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
     pub fn from_env() -> Result<Self, VarError> {
         let key = Key::from_env()?;
         let secret = Secret::from_env()?;
-        let bucket = Bucket::from_env().ok();
 
-        Ok(Client {
-            key,
-            secret,
-            bucket,
-        })
+        let mut client = Client::new(key, secret);
+        client.bucket = Bucket::from_env().ok();
+        Ok(client)
+    }
+
+    /// 更换为固定的访问凭证
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.set_credentials_provider(StaticProvider::new(credentials));
+    }
+
+    /// 更换凭证的来源，之前缓存的凭证会被丢弃
+    pub fn set_credentials_provider<P: CredentialsProvider + 'static>(&mut self, provider: P) {
+        self.credentials = Arc::new(CachedProvider::new(provider));
+    }
+
+    /// 获取当前的访问凭证，优先使用缓存
+    pub async fn credentials(&self) -> Result<Credentials, OssError> {
+        self.credentials.provide().await
     }
 
     /// 设置默认的 bucket(bucket 也会包含 endpoint 信息)
@@ -68,45 +147,244 @@ impl Client {
         self.bucket.as_ref()
     }
 
-    pub(crate) fn authorization(
+    /// 设置请求的重试策略，返回之前的策略
+    /// ```
+    /// # use aliyun_oss_client::{Client, Key, Secret};
+    /// # use aliyun_oss_client::client::RetryPolicy;
+    /// let mut client = Client::new(Key::new("foo"), Secret::new("bar"));
+    /// client.set_retry_policy(RetryPolicy::none());
+    /// assert_eq!(client.retry_policy().get_max_attempts(), 1);
+    /// ```
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) -> RetryPolicy {
+        std::mem::replace(&mut self.retry, policy)
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// 使用自定义的 HTTP 客户端，返回之前的客户端
+    ///
+    /// 可以通过 [`HttpClientBuilder`] 生成，也可以直接传入已有的 `reqwest::Client`
+    pub fn set_http_client(&mut self, http: reqwest::Client) -> reqwest::Client {
+        std::mem::replace(&mut self.http, http)
+    }
+
+    /// 所有请求共用的 HTTP 客户端
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// 设置签名版本，返回之前的版本
+    /// ```
+    /// # use aliyun_oss_client::{Client, Key, Secret};
+    /// # use aliyun_oss_client::client::SignVersion;
+    /// let mut client = Client::new(Key::new("foo"), Secret::new("bar"));
+    /// client.set_sign_version(SignVersion::V4);
+    /// client.set_region("cn-hangzhou");
+    /// assert_eq!(client.sign_version(), SignVersion::V4);
+    /// ```
+    pub fn set_sign_version(&mut self, version: SignVersion) -> SignVersion {
+        std::mem::replace(&mut self.sign_version, version)
+    }
+
+    pub fn sign_version(&self) -> SignVersion {
+        self.sign_version
+    }
+
+    /// V4 签名使用的地域，如 `cn-hangzhou`，不设置时从请求的域名中获取
+    pub fn set_region<R: Into<String>>(&mut self, region: R) -> Option<String> {
+        self.region.replace(region.into())
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// V4 签名时额外参与签名的头信息，如 `host`
+    ///
+    /// `Content-Type`、`Content-MD5` 和以 `x-oss-` 开头的头信息总会参与签名，不需要设置
+    pub fn set_additional_headers<I, H>(&mut self, headers: I)
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.additional_headers = headers.into_iter().map(Into::into).collect();
+    }
+
+    pub(crate) fn request(
         &self,
         method: Method,
+        url: Url,
         resource: CanonicalizedResource,
+    ) -> Request<'_> {
+        #[cfg(test)]
+        let url = match &self.test_server {
+            Some(server) => {
+                let mut url = url;
+                url.set_scheme(server.scheme()).unwrap();
+                url.set_host(server.host_str()).unwrap();
+                url.set_port(server.port()).unwrap();
+                url
+            }
+            None => url,
+        };
+        Request::new(self, method, url, resource)
+    }
+
+    /// 之后的请求都发往 `server`，只替换协议、域名和端口
+    #[cfg(test)]
+    pub(crate) fn set_test_server(&mut self, server: &str) {
+        self.test_server = Some(server.parse().unwrap());
+    }
+
+    /// 按 client 的签名版本生成请求头，`headers` 中的 `Content-Type`、`Content-MD5`
+    /// 和以 `x-oss-` 开头的头信息会加入签名，没有 `Content-Type` 时签名中该行为空，
+    /// 返回的头信息也会包含 `headers` 中的全部内容
+    pub(crate) fn authorization_with_headers(
+        &self,
+        credentials: &Credentials,
+        method: Method,
+        url: &Url,
+        resource: CanonicalizedResource,
+        mut headers: HeaderMap,
     ) -> Result<HeaderMap, OssError> {
-        const LINE_BREAK: &str = "\n";
+        if let Some(token) = credentials.security_token() {
+            headers.insert(SECURITY_TOKEN, token.try_into()?);
+        }
 
-        let date = now();
-        let content_type = "text/xml";
+        match self.sign_version {
+            SignVersion::V1 => {
+                Self::authorization_v1(credentials, method, resource.canonicalize(url), headers)
+            }
+            SignVersion::V4 => {
+                // `Date` 与签名中的 `x-oss-date` 使用同一个时间
+                let now = Utc::now();
+                headers.insert(
+                    "Date",
+                    now.format("%a, %d %b %Y %T GMT").to_string().try_into()?,
+                );
+
+                let signer = self.v4_signer(credentials, url)?;
+                let sign = signer.authorization(&method, url, &resource, &mut headers, now)?;
+                headers.insert("Authorization", sign.try_into()?);
+                Ok(headers)
+            }
+        }
+    }
+
+    /// # 生成预签名 URL
+    ///
+    /// 签名信息和过期时间都放在查询参数中，使用 STS 临时凭证时也会带上 security token。
+    /// `headers` 中的 `Content-Type`、`Content-MD5` 和以 `x-oss-` 开头的头信息会参与签名，
+    /// 使用 URL 时需要发送相同的头信息。
+    ///
+    /// V4 签名的有效期最长为 7 天，超过时返回 [`OssError::InvalidConfig`]
+    pub(crate) async fn presign(
+        &self,
+        method: Method,
+        mut url: Url,
+        resource: CanonicalizedResource,
+        mut headers: HeaderMap,
+        expires: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Url, OssError> {
+        if self.sign_version == SignVersion::V4 && expires > MAX_PRESIGN_EXPIRES_V4 {
+            return Err(OssError::InvalidConfig(format!(
+                "presign expires must not exceed {} seconds",
+                MAX_PRESIGN_EXPIRES_V4.as_secs()
+            )));
+        }
+        let credentials = self.credentials().await?;
+
+        match self.sign_version {
+            SignVersion::V1 => {
+                if let Some(token) = credentials.security_token() {
+                    append_query(&mut url, "security-token", token);
+                }
+                let expires = (now.timestamp() as u64 + expires.as_secs()).to_string();
+                let string =
+                    string_to_sign_v1(&method, &headers, &expires, &resource.canonicalize(&url))?;
+                let signature = credentials.secret().encryption(string.as_bytes()).unwrap();
+
+                append_query(&mut url, "OSSAccessKeyId", credentials.key().as_str());
+                append_query(&mut url, "Expires", &expires);
+                append_query(&mut url, "Signature", &signature);
+            }
+            SignVersion::V4 => {
+                if let Some(token) = credentials.security_token() {
+                    append_query(&mut url, SECURITY_TOKEN, token);
+                }
+                let region = self.v4_region(&url)?.to_owned();
+                let signer = v4::Signer {
+                    credentials: &credentials,
+                    region: &region,
+                    additional_headers: &self.additional_headers,
+                };
+                signer.presign(
+                    &method,
+                    &mut url,
+                    &resource,
+                    &mut headers,
+                    now,
+                    expires.as_secs(),
+                )?;
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// V4 签名使用的地域，没有设置时从请求的域名中获取
+    fn v4_region<'a>(&'a self, url: &'a Url) -> Result<&'a str, OssError> {
+        match &self.region {
+            Some(region) => Ok(region.as_str()),
+            None => url
+                .host_str()
+                .and_then(v4::region_from_host)
+                .ok_or_else(|| {
+                    OssError::InvalidConfig("region is required for v4 signature".to_owned())
+                }),
+        }
+    }
+
+    fn v4_signer<'a>(
+        &'a self,
+        credentials: &'a Credentials,
+        url: &'a Url,
+    ) -> Result<v4::Signer<'a>, OssError> {
+        Ok(v4::Signer {
+            credentials,
+            region: self.v4_region(url)?,
+            additional_headers: &self.additional_headers,
+        })
+    }
 
+    /// `OSS AccessKeyId:Signature` 格式的签名
+    fn authorization_v1(
+        credentials: &Credentials,
+        method: Method,
+        resource: CanonicalizedResource,
+        mut headers: HeaderMap,
+    ) -> Result<HeaderMap, OssError> {
+        let date = now();
         let sign = {
-            let mut string = method.as_str().to_owned();
-            string += LINE_BREAK;
-            string += LINE_BREAK;
-            string += content_type;
-            string += LINE_BREAK;
-            string += date.as_str();
-            string += LINE_BREAK;
-            string += resource.as_str();
-
-            let encry = self.secret.encryption(string.as_bytes()).unwrap();
-
-            format!("OSS {}:{}", self.key.as_str(), encry)
-        };
-        let header_map = {
-            let mut headers = HeaderMap::new();
-            headers.insert("AccessKeyId", self.key.as_str().try_into()?);
-            headers.insert("VERB", method.as_str().try_into()?);
-            headers.insert("Date", date.try_into()?);
-            headers.insert("Authorization", sign.try_into()?);
-            headers.insert(CONTENT_TYPE, content_type.try_into()?);
-            headers.insert(
-                "CanonicalizedResource",
-                resource.as_str().try_into().unwrap(),
-            );
-            headers
+            let string = string_to_sign_v1(&method, &headers, &date, &resource)?;
+            let encry = credentials.secret().encryption(string.as_bytes()).unwrap();
+
+            format!("OSS {}:{}", credentials.key().as_str(), encry)
         };
 
-        Ok(header_map)
+        headers.insert("AccessKeyId", credentials.key().as_str().try_into()?);
+        headers.insert("VERB", method.as_str().try_into()?);
+        headers.insert("Date", date.try_into()?);
+        headers.insert("Authorization", sign.try_into()?);
+        headers.insert(
+            "CanonicalizedResource",
+            resource.as_str().try_into().unwrap(),
+        );
+
+        Ok(headers)
     }
 
     /// 调用 api 导出 bucket 列表信息到自定义类型
@@ -153,15 +431,8 @@ impl Client {
         let method = Method::GET;
         let resource = CanonicalizedResource::default();
 
-        let header_map = self.authorization(method, resource)?;
-
-        let content = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let response = self.request(method, url, resource).send().await?;
+        let content = check_response(response).await?.text().await?;
 
         //println!("{}", content);
 
@@ -187,15 +458,8 @@ impl Client {
         let method = Method::GET;
         let resource = CanonicalizedResource::default();
 
-        let header_map = self.authorization(method, resource)?;
-
-        let content = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let response = self.request(method, url, resource).send().await?;
+        let content = check_response(response).await?.text().await?;
 
         // println!("{content}");
 
@@ -232,6 +496,61 @@ impl Client {
     }
 }
 
+fn default_http_client() -> reqwest::Client {
+    HttpClientBuilder::new().build().unwrap_or_default()
+}
+
+/// 按名称排序的 `x-oss-` 头信息，每一项的格式为 `name:value\n`
+fn canonicalized_oss_headers(headers: &HeaderMap) -> Result<String, OssError> {
+    let mut list = Vec::new();
+    for (name, value) in headers.iter() {
+        if name.as_str().starts_with("x-oss-") {
+            list.push((name.as_str(), value.to_str()?.trim()));
+        }
+    }
+    list.sort();
+
+    let mut string = String::new();
+    for (name, value) in list {
+        string += name;
+        string += ":";
+        string += value;
+        string += "\n";
+    }
+    Ok(string)
+}
+
+/// V1 签名的字符串，`date` 在预签名 URL 中为过期时间的时间戳
+fn string_to_sign_v1(
+    method: &Method,
+    headers: &HeaderMap,
+    date: &str,
+    resource: &CanonicalizedResource,
+) -> Result<String, OssError> {
+    const LINE_BREAK: &str = "\n";
+
+    let content_type = match headers.get(CONTENT_TYPE) {
+        Some(content_type) => content_type.to_str()?,
+        None => "",
+    };
+    let content_md5 = match headers.get(CONTENT_MD5) {
+        Some(md5) => md5.to_str()?,
+        None => "",
+    };
+
+    let mut string = method.as_str().to_owned();
+    string += LINE_BREAK;
+    string += content_md5;
+    string += LINE_BREAK;
+    string += content_type;
+    string += LINE_BREAK;
+    string += date;
+    string += LINE_BREAK;
+    string += &canonicalized_oss_headers(headers)?;
+    string += resource.as_str();
+    Ok(string)
+}
+
 fn now() -> String {
     Utc::now().format("%a, %d %b %Y %T GMT").to_string()
 }
@@ -242,9 +561,211 @@ fn now() -> String {
 //     DateTime::from_utc(naive, Utc)
 // }
 
+#[cfg(test)]
+pub fn init_client() -> Client {
+    use std::env;
+
+    use dotenv::dotenv;
+
+    dotenv().ok();
+    let key = env::var("ALIYUN_KEY_ID").unwrap();
+    let secret = env::var("ALIYUN_KEY_SECRET").unwrap();
+
+    Client::new(Key::new(key), Secret::new(secret))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{client::init_client, types::EndPoint};
+    use chrono::DateTime;
+    use reqwest::{header::HeaderMap, Method};
+
+    use super::canonicalized_oss_headers;
+    use crate::{
+        client::{init_client, Client, SignVersion},
+        credentials::Credentials,
+        error::OssError,
+        types::{CanonicalizedResource, EndPoint, Key, Secret},
+    };
+
+    #[test]
+    fn test_canonicalized_oss_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-oss-meta-b", " 2 ".parse().unwrap());
+        headers.insert("X-OSS-Copy-Source", "/foo/a.txt".parse().unwrap());
+        headers.insert("content-type", "text/plain".parse().unwrap());
+
+        assert_eq!(
+            canonicalized_oss_headers(&headers).unwrap(),
+            "x-oss-copy-source:/foo/a.txt\nx-oss-meta-b:2\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_security_token() {
+        let credentials = Credentials::new(Key::new("STS.foo"), Secret::new("bar"))
+            .with_security_token("CAIS-token");
+        let client = Client::from_credentials(credentials);
+        let credentials = client.credentials().await.unwrap();
+
+        let resource = CanonicalizedResource::new("/foo/a.txt".to_owned());
+        let headers = client
+            .authorization_with_headers(
+                &credentials,
+                Method::GET,
+                &"https://foo.oss-cn-qingdao.aliyuncs.com/a.txt"
+                    .parse()
+                    .unwrap(),
+                resource,
+                HeaderMap::new(),
+            )
+            .unwrap();
+        assert_eq!(headers.get("x-oss-security-token").unwrap(), "CAIS-token");
+
+        let date = headers.get("Date").unwrap().to_str().unwrap();
+        let string = format!(
+            "GET\n\n\n{}\nx-oss-security-token:CAIS-token\n/foo/a.txt",
+            date
+        );
+        let sign = format!(
+            "OSS STS.foo:{}",
+            Secret::new("bar").encryption(string.as_bytes()).unwrap()
+        );
+        assert_eq!(headers.get("Authorization").unwrap(), sign.as_str());
+    }
+
+    #[test]
+    fn test_v1_put_without_content_type() {
+        let credentials = Credentials::new(Key::new("foo"), Secret::new("bar"));
+        let client = Client::from_credentials(credentials.clone());
+
+        let headers = client
+            .authorization_with_headers(
+                &credentials,
+                Method::PUT,
+                &"https://b.oss-cn-qingdao.aliyuncs.com/a.txt"
+                    .parse()
+                    .unwrap(),
+                CanonicalizedResource::new("/b/a.txt".to_owned()),
+                HeaderMap::new(),
+            )
+            .unwrap();
+        assert!(headers.get("Content-Type").is_none());
+
+        let date = headers.get("Date").unwrap().to_str().unwrap();
+        let string = format!("PUT\n\n\n{}\n/b/a.txt", date);
+        let sign = format!(
+            "OSS foo:{}",
+            Secret::new("bar").encryption(string.as_bytes()).unwrap()
+        );
+        assert_eq!(headers.get("Authorization").unwrap(), sign.as_str());
+    }
+
+    #[test]
+    fn test_v1_canonicalize() {
+        let credentials = Credentials::new(Key::new("foo"), Secret::new("bar"));
+        let client = Client::from_credentials(credentials.clone());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "image/png".parse().unwrap());
+        headers.insert("Content-MD5", "eB5eJF1ptWaXm4bijSPyxw==".parse().unwrap());
+        headers.insert("X-OSS-Meta-Author", "foo".parse().unwrap());
+        headers.insert("x-oss-object-acl", "private".parse().unwrap());
+
+        let url = "https://b.oss-cn-qingdao.aliyuncs.com/a.png?uploadId=1&partNumber=2&x=3"
+            .parse()
+            .unwrap();
+        let resource = CanonicalizedResource::new("/b/a.png".to_owned());
+        let headers = client
+            .authorization_with_headers(&credentials, Method::PUT, &url, resource, headers)
+            .unwrap();
+
+        let date = headers.get("Date").unwrap().to_str().unwrap();
+        let string = format!(
+            "PUT\neB5eJF1ptWaXm4bijSPyxw==\nimage/png\n{}\n\
+             x-oss-meta-author:foo\nx-oss-object-acl:private\n\
+             /b/a.png?partNumber=2&uploadId=1",
+            date
+        );
+        let sign = format!(
+            "OSS foo:{}",
+            Secret::new("bar").encryption(string.as_bytes()).unwrap()
+        );
+        assert_eq!(headers.get("Authorization").unwrap(), sign.as_str());
+        assert_eq!(headers.get("Content-Type").unwrap(), "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_v4_date() {
+        let mut client = Client::new(Key::new("foo"), Secret::new("bar"));
+        client.set_sign_version(SignVersion::V4);
+        let credentials = client.credentials().await.unwrap();
+
+        let headers = client
+            .authorization_with_headers(
+                &credentials,
+                Method::GET,
+                &"https://foo.oss-cn-qingdao.aliyuncs.com/a.txt"
+                    .parse()
+                    .unwrap(),
+                CanonicalizedResource::new("/foo/a.txt".to_owned()),
+                HeaderMap::new(),
+            )
+            .unwrap();
+        let date = headers.get("Date").unwrap().to_str().unwrap();
+        let date = DateTime::parse_from_rfc2822(date).unwrap();
+        assert_eq!(
+            headers.get("x-oss-date").unwrap(),
+            date.format("%Y%m%dT%H%M%SZ").to_string().as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_v4_region() {
+        let mut client = Client::new(Key::new("foo"), Secret::new("bar"));
+        client.set_sign_version(SignVersion::V4);
+        let credentials = client.credentials().await.unwrap();
+        let resource = CanonicalizedResource::new("/foo/a.txt".to_owned());
+
+        let headers = client
+            .authorization_with_headers(
+                &credentials,
+                Method::GET,
+                &"https://foo.oss-cn-qingdao.aliyuncs.com/a.txt"
+                    .parse()
+                    .unwrap(),
+                resource.clone(),
+                HeaderMap::new(),
+            )
+            .unwrap();
+        let authorization = headers.get("Authorization").unwrap().to_str().unwrap();
+        assert!(
+            authorization.starts_with("OSS4-HMAC-SHA256 Credential=foo/")
+                && authorization.contains("/cn-qingdao/oss/aliyun_v4_request,Signature=")
+        );
+
+        let url = "http://127.0.0.1:8080/a.txt".parse().unwrap();
+        let err = client
+            .authorization_with_headers(
+                &credentials,
+                Method::GET,
+                &url,
+                resource.clone(),
+                HeaderMap::new(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, OssError::InvalidConfig(_)));
+
+        client.set_region("cn-hangzhou");
+        let headers = client
+            .authorization_with_headers(&credentials, Method::GET, &url, resource, HeaderMap::new())
+            .unwrap();
+        assert!(headers
+            .get("Authorization")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("/cn-hangzhou/oss/aliyun_v4_request,"));
+    }
 
     #[tokio::test]
     async fn test_get_buckets() {
@@ -280,16 +801,3 @@ mod tests {
         println!("{list:?}");
     }
 }
-
-#[cfg(test)]
-pub fn init_client() -> Client {
-    use std::env;
-
-    use dotenv::dotenv;
-
-    dotenv().ok();
-    let key = env::var("ALIYUN_KEY_ID").unwrap();
-    let secret = env::var("ALIYUN_KEY_SECRET").unwrap();
-
-    Client::new(Key::new(key), Secret::new(secret))
-}
@@ -0,0 +1,361 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    bucket::Bucket,
+    client::{Client, HttpClientBuilder, RetryPolicy, SignVersion},
+    credentials::{CachedProvider, Credentials, CredentialsProvider, StaticProvider},
+    error::OssError,
+    types::{EndPoint, Key, Secret},
+};
+
+/// # 创建 [`Client`] 的构建器
+///
+/// 所有配置在 [`build`](ClientBuilder::build) 时统一校验，配置有误时返回
+/// [`OssError::InvalidConfig`]，不会等到发送请求时才 panic
+///
+/// ```
+/// # use std::{sync::Arc, time::Duration};
+/// # use aliyun_oss_client::{Client, EndPoint, Key, Secret};
+/// # use aliyun_oss_client::client::RetryPolicy;
+/// # fn run() -> Result<(), aliyun_oss_client::error::OssError> {
+/// let client = Client::builder()
+///     .key(Key::new("foo"))
+///     .secret(Secret::new("bar"))
+///     .bucket("my-bucket")
+///     .endpoint(EndPoint::CN_QINGDAO)
+///     .internal(true)
+///     .connect_timeout(Duration::from_secs(3))
+///     .retry_policy(RetryPolicy::new().max_attempts(5))
+///     .build()?;
+///
+/// assert!(client.bucket().is_some());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ClientBuilder {
+    key: Option<Key>,
+    secret: Option<Secret>,
+    credentials: Option<Credentials>,
+    provider: Option<Arc<dyn CredentialsProvider>>,
+    bucket: Option<String>,
+    endpoint: Option<Endpoint>,
+    internal: Option<bool>,
+    http: HttpClientBuilder,
+    http_options: bool,
+    http_client: Option<reqwest::Client>,
+    retry: RetryPolicy,
+    sign_version: SignVersion,
+    region: Option<String>,
+    additional_headers: Vec<String>,
+}
+
+/// 传入的 endpoint，字符串形式的在 build 时解析
+#[derive(Debug)]
+enum Endpoint {
+    Value(EndPoint),
+    Str(String),
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn key(mut self, key: Key) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn secret(mut self, secret: Secret) -> Self {
+        self.secret = Some(secret);
+        self
+    }
+
+    /// 使用完整的访问凭证（如 STS 临时凭证），会忽略 `key` 和 `secret`
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// 从 [`CredentialsProvider`] 获取凭证，不能与 `key`、`secret`、`credentials` 同时使用
+    pub fn credentials_provider<P: CredentialsProvider + 'static>(mut self, provider: P) -> Self {
+        self.provider = Some(Arc::new(CachedProvider::new(provider)));
+        self
+    }
+
+    /// 默认 bucket 的名称，需要同时设置 endpoint
+    pub fn bucket<N: Into<String>>(mut self, name: N) -> Self {
+        self.bucket = Some(name.into());
+        self
+    }
+
+    pub fn endpoint(mut self, endpoint: EndPoint) -> Self {
+        self.endpoint = Some(Endpoint::Value(endpoint));
+        self
+    }
+
+    /// 以字符串设置 endpoint，如 `cn-qingdao`，格式与 [`EndPoint::new`] 相同
+    pub fn endpoint_str<E: Into<String>>(mut self, endpoint: E) -> Self {
+        self.endpoint = Some(Endpoint::Str(endpoint.into()));
+        self
+    }
+
+    /// 是否使用内网地址，会覆盖 endpoint 中的设置
+    pub fn internal(mut self, is_internal: bool) -> Self {
+        self.internal = Some(is_internal);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http = self.http.connect_timeout(timeout);
+        self.http_options = true;
+        self
+    }
+
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.http = self.http.read_timeout(timeout);
+        self.http_options = true;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http = self.http.timeout(timeout);
+        self.http_options = true;
+        self
+    }
+
+    pub fn user_agent<U: Into<String>>(mut self, user_agent: U) -> Self {
+        self.http = self.http.user_agent(user_agent);
+        self.http_options = true;
+        self
+    }
+
+    /// 完整的 HTTP 配置，会覆盖之前设置的超时时间和 User-Agent
+    pub fn http(mut self, http: HttpClientBuilder) -> Self {
+        self.http = http;
+        self.http_options = true;
+        self
+    }
+
+    /// 使用已有的 `reqwest::Client`，不能与超时时间等 HTTP 配置同时使用
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http_client = Some(http);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn sign_version(mut self, version: SignVersion) -> Self {
+        self.sign_version = version;
+        self
+    }
+
+    /// V4 签名使用的地域，如 `cn-hangzhou`，不设置时从 endpoint 中获取
+    pub fn region<R: Into<String>>(mut self, region: R) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// V4 签名时额外参与签名的头信息
+    pub fn additional_headers<I, H>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.additional_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn build(self) -> Result<Client, OssError> {
+        let has_static = self.credentials.is_some() || self.key.is_some() || self.secret.is_some();
+        let provider = match self.provider {
+            Some(_) if has_static => {
+                return Err(invalid(
+                    "credentials provider and static credentials can not be used together",
+                ))
+            }
+            Some(provider) => provider,
+            None => {
+                let credentials = match self.credentials {
+                    Some(credentials) => credentials,
+                    None => Credentials::new(
+                        self.key.unwrap_or_else(|| Key::new("")),
+                        self.secret.unwrap_or_else(|| Secret::new("")),
+                    ),
+                };
+                if credentials.key().as_str().is_empty() {
+                    return Err(invalid("access key id is missing"));
+                }
+                if credentials.secret().0.is_empty() {
+                    return Err(invalid("access key secret is missing"));
+                }
+                if credentials.is_expired() {
+                    return Err(invalid("credentials are expired"));
+                }
+                Arc::new(CachedProvider::new(StaticProvider::new(credentials)))
+            }
+        };
+
+        let endpoint = match self.endpoint {
+            Some(Endpoint::Value(endpoint)) => Some(endpoint),
+            Some(Endpoint::Str(endpoint)) => Some(
+                EndPoint::new(&endpoint)
+                    .map_err(|_| invalid(format!("invalid endpoint: {:?}", endpoint)))?,
+            ),
+            None => None,
+        };
+        let endpoint = endpoint.map(|mut endpoint| {
+            if let Some(is_internal) = self.internal {
+                endpoint.set_internal(is_internal);
+            }
+            endpoint
+        });
+        if let Some(endpoint) = &endpoint {
+            endpoint
+                .try_to_url()
+                .map_err(|_| invalid(format!("invalid endpoint: {}", endpoint.as_ref())))?;
+        }
+
+        let region = self
+            .region
+            .or_else(|| endpoint.as_ref().map(|e| e.as_ref().to_owned()));
+        if let Some(region) = &region {
+            if region.is_empty()
+                || !region
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-')
+            {
+                return Err(invalid(format!("invalid region: {:?}", region)));
+            }
+        }
+
+        let bucket = match (self.bucket, endpoint) {
+            (Some(name), Some(endpoint)) => {
+                let bucket = Bucket::new(name, endpoint);
+                bucket
+                    .try_to_url()
+                    .map_err(|_| invalid(format!("invalid bucket name: {:?}", bucket.as_str())))?;
+                Some(bucket)
+            }
+            (Some(_), None) => return Err(invalid("bucket is set but endpoint is missing")),
+            (None, Some(_)) => return Err(invalid("endpoint is set but bucket is missing")),
+            (None, None) => None,
+        };
+
+        let http = match self.http_client {
+            Some(_) if self.http_options => {
+                return Err(invalid(
+                    "http client and http options can not be used together",
+                ))
+            }
+            Some(http) => http,
+            None => self.http.build()?,
+        };
+
+        let mut client = Client::with_provider(provider);
+        client.bucket = bucket;
+        client.retry = self.retry;
+        client.http = http;
+        client.sign_version = self.sign_version;
+        client.region = region;
+        client.additional_headers = self.additional_headers;
+
+        Ok(client)
+    }
+}
+
+fn invalid<M: Into<String>>(message: M) -> OssError {
+    OssError::InvalidConfig(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::{Client, HttpClientBuilder, SignVersion},
+        credentials::EnvProvider,
+        error::OssError,
+        types::{EndPoint, Key, Secret},
+    };
+
+    fn message(error: OssError) -> String {
+        match error {
+            OssError::InvalidConfig(message) => message,
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let client = Client::builder()
+            .key(Key::new("foo"))
+            .secret(Secret::new("bar"))
+            .bucket("foo-bucket")
+            .endpoint_str("qingdao")
+            .internal(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.bucket().unwrap().to_url().as_str(),
+            "https://foo-bucket.oss-cn-qingdao-internal.aliyuncs.com/"
+        );
+
+        let err = Client::builder().secret(Secret::new("bar")).build();
+        assert_eq!(message(err.unwrap_err()), "access key id is missing");
+
+        let builder = || {
+            Client::builder()
+                .key(Key::new("foo"))
+                .secret(Secret::new("bar"))
+        };
+        let err = builder()
+            .bucket("Foo")
+            .endpoint(EndPoint::CN_QINGDAO)
+            .build();
+        assert_eq!(message(err.unwrap_err()), "invalid bucket name: \"Foo\"");
+
+        let err = builder().bucket("foo").endpoint_str("oss-cn-jinan").build();
+        assert_eq!(
+            message(err.unwrap_err()),
+            "invalid endpoint: \"oss-cn-jinan\""
+        );
+
+        let err = builder().bucket("foo").build();
+        assert_eq!(
+            message(err.unwrap_err()),
+            "bucket is set but endpoint is missing"
+        );
+
+        let err = builder().credentials_provider(EnvProvider::new()).build();
+        assert_eq!(
+            message(err.unwrap_err()),
+            "credentials provider and static credentials can not be used together"
+        );
+        assert!(Client::builder()
+            .credentials_provider(EnvProvider::new())
+            .build()
+            .is_ok());
+
+        let client = builder()
+            .bucket("foo")
+            .endpoint(EndPoint::CN_SHANGHAI)
+            .sign_version(SignVersion::V4)
+            .build()
+            .unwrap();
+        assert_eq!(client.region(), Some("cn-shanghai"));
+        let err = builder().region("cn hangzhou").build();
+        assert_eq!(message(err.unwrap_err()), "invalid region: \"cn hangzhou\"");
+
+        let err = builder()
+            .user_agent("my-app/1.0")
+            .http_client(HttpClientBuilder::new().build().unwrap())
+            .build();
+        assert_eq!(
+            message(err.unwrap_err()),
+            "http client and http options can not be used together"
+        );
+    }
+}
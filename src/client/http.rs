@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use reqwest::{Certificate, Proxy};
+
+use crate::error::OssError;
+
+/// 默认的 User-Agent
+pub const DEFAULT_USER_AGENT: &str = concat!("aliyun-oss-client/", env!("CARGO_PKG_VERSION"));
+
+/// # HTTP 客户端的配置
+///
+/// 生成的 `reqwest::Client` 由 [`Client`](crate::Client) 持有，所有请求共用连接池和 TLS 会话
+///
+/// ```
+/// # use std::time::Duration;
+/// # use aliyun_oss_client::{Client, Key, Secret};
+/// # use aliyun_oss_client::client::HttpClientBuilder;
+/// # fn run() -> Result<(), aliyun_oss_client::error::OssError> {
+/// let http = HttpClientBuilder::new()
+///     .connect_timeout(Duration::from_secs(3))
+///     .read_timeout(Duration::from_secs(30))
+///     .pool_max_idle_per_host(16)
+///     .user_agent("my-app/1.0")
+///     .build()?;
+///
+/// let mut client = Client::new(Key::new("foo"), Secret::new("bar"));
+/// client.set_http_client(http);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpClientBuilder {
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    proxy: Option<Proxy>,
+    root_certificates: Vec<Certificate>,
+    http2: bool,
+    user_agent: String,
+}
+
+impl Default for HttpClientBuilder {
+    fn default() -> Self {
+        HttpClientBuilder {
+            connect_timeout: None,
+            read_timeout: None,
+            timeout: None,
+            pool_max_idle_per_host: None,
+            proxy: None,
+            root_certificates: Vec::new(),
+            http2: true,
+            user_agent: DEFAULT_USER_AGENT.to_owned(),
+        }
+    }
+}
+
+impl HttpClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 建立连接的超时时间
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// 两次读取响应数据之间的超时时间，适合下载大文件时使用
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// 整个请求（包括读取响应体）的超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 每个域名保留的最大空闲连接数
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// 添加信任的根证书，可以多次调用
+    pub fn add_root_certificate(mut self, cert: Certificate) -> Self {
+        self.root_certificates.push(cert);
+        self
+    }
+
+    /// 是否允许使用 HTTP/2，关闭后只使用 HTTP/1.1，默认开启
+    pub fn http2(mut self, enable: bool) -> Self {
+        self.http2 = enable;
+        self
+    }
+
+    pub fn user_agent<U: Into<String>>(mut self, user_agent: U) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn build(self) -> Result<reqwest::Client, OssError> {
+        let mut builder = reqwest::Client::builder().user_agent(self.user_agent);
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        for cert in self.root_certificates {
+            builder = builder.add_root_certificate(cert);
+        }
+        if !self.http2 {
+            builder = builder.http1_only();
+        }
+
+        Ok(builder.build()?)
+    }
+}
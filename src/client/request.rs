@@ -0,0 +1,193 @@
+use bytes::Bytes;
+use reqwest::{header::HeaderMap, Body, Method, Response};
+use url::Url;
+
+use crate::{
+    client::{Client, RetryPolicy},
+    error::OssError,
+    types::CanonicalizedResource,
+};
+
+/// 发往 OSS 的请求，发送时才进行签名
+pub(crate) struct Request<'a> {
+    client: &'a Client,
+    method: Method,
+    url: Url,
+    resource: CanonicalizedResource,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+}
+
+impl<'a> Request<'a> {
+    pub(crate) fn new(
+        client: &'a Client,
+        method: Method,
+        url: Url,
+        resource: CanonicalizedResource,
+    ) -> Self {
+        Request {
+            client,
+            method,
+            url,
+            resource,
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    /// 追加请求头，其中的 `Content-MD5` 和 `x-oss-` 头信息会加入签名
+    pub(crate) fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    pub(crate) fn body<B: Into<Bytes>>(mut self, body: B) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// 发送请求，幂等的请求按 client 的重试策略重试，每次都会重新签名
+    ///
+    /// 返回的响应没有检查状态码
+    pub(crate) async fn send(self) -> Result<Response, OssError> {
+        let policy = self.client.retry_policy();
+        let idempotent = self.method.is_idempotent();
+
+        let mut attempt = 1;
+        loop {
+            let retryable = idempotent && attempt < policy.get_max_attempts();
+            let body = self.body.clone().map(Body::from);
+
+            match self.send_once(body).await {
+                Ok(response)
+                    if retryable && RetryPolicy::is_retryable_status(response.status()) => {}
+                Err(OssError::Reqwest(e)) if retryable && RetryPolicy::is_retryable_error(&e) => {}
+                res => return res,
+            }
+
+            tokio::time::sleep(policy.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// 以流的形式发送请求体，流只能读取一次，所以不会重试
+    pub(crate) async fn send_stream(self, body: Body) -> Result<Response, OssError> {
+        self.send_once(Some(body)).await
+    }
+
+    async fn send_once(&self, body: Option<Body>) -> Result<Response, OssError> {
+        let credentials = self.client.credentials().await?;
+        let header_map = self.client.authorization_with_headers(
+            &credentials,
+            self.method.clone(),
+            &self.url,
+            self.resource.clone(),
+            self.headers.clone(),
+        )?;
+
+        let mut request = self
+            .client
+            .http_client()
+            .request(self.method.clone(), self.url.clone())
+            .headers(header_map);
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+
+        Ok(request.send().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::JoinHandle, time::Duration};
+
+    use reqwest::{Method, StatusCode};
+
+    use crate::{
+        client::{Client, HttpClientBuilder, RetryPolicy},
+        test_server::{self, Request},
+        types::{CanonicalizedResource, Key, Secret},
+    };
+
+    /// 依次返回 `statuses` 中的状态码，请求地址为 `/foo.txt`
+    fn serve(statuses: Vec<u16>) -> (String, JoinHandle<Vec<Request>>) {
+        let (url, handle) =
+            test_server::serve(statuses.into_iter().map(|s| (s, String::new())).collect());
+        (format!("{}/foo.txt", url), handle)
+    }
+
+    fn client(policy: RetryPolicy) -> Client {
+        let mut client = Client::new(Key::new("foo"), Secret::new("bar"));
+        client.set_retry_policy(policy.base_delay(Duration::from_millis(1)));
+        client
+    }
+
+    #[tokio::test]
+    async fn test_retry_server_error() {
+        let (url, handle) = serve(vec![503, 500, 200]);
+
+        let response = client(RetryPolicy::new().max_attempts(3))
+            .request(
+                Method::GET,
+                url.parse().unwrap(),
+                CanonicalizedResource::new("/bucket/foo.txt".to_owned()),
+            )
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.header("authorization").unwrap().starts_with("OSS foo:")));
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_for_post() {
+        let (url, handle) = serve(vec![503]);
+
+        let response = client(RetryPolicy::new().max_attempts(3))
+            .request(
+                Method::POST,
+                url.parse().unwrap(),
+                CanonicalizedResource::new("/bucket/foo.txt?append&position=0".to_owned()),
+            )
+            .body("content")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(handle.join().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client() {
+        let (url, handle) = serve(vec![200]);
+
+        let mut client = client(RetryPolicy::none());
+        client.set_http_client(
+            HttpClientBuilder::new()
+                .user_agent("my-app/1.0")
+                .build()
+                .unwrap(),
+        );
+        client
+            .request(
+                Method::GET,
+                url.parse().unwrap(),
+                CanonicalizedResource::new("/bucket/foo.txt".to_owned()),
+            )
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            handle.join().unwrap()[0].header("user-agent"),
+            Some("my-app/1.0")
+        );
+    }
+}
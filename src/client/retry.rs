@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+/// # 请求的重试策略
+///
+/// 只有幂等的请求（GET、HEAD、PUT、DELETE）会被重试，触发重试的情况包括连接失败、超时、
+/// 5xx 以及 429 限流。两次重试之间的等待时间按指数增长，不超过 `max_delay`
+///
+/// ```
+/// # use std::time::Duration;
+/// # use aliyun_oss_client::client::RetryPolicy;
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .base_delay(Duration::from_millis(100))
+///     .max_delay(Duration::from_secs(5))
+///     .jitter(true);
+/// assert_eq!(policy.get_max_attempts(), 5);
+///
+/// // 不重试
+/// assert_eq!(RetryPolicy::none().get_max_attempts(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 默认最多请求 3 次，初始等待 200ms，最长等待 10s，开启随机抖动
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只请求一次，不重试
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// 包含第一次请求在内的最多请求次数，最小为 1
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// 第一次重试前的等待时间
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// 每次重试前等待时间的上限
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// 开启后，等待时间在 0 到计算结果之间随机选取，避免大量请求同时重试
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// 第 `attempt` 次请求失败后，发起下一次请求前的等待时间
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exp).min(self.max_delay);
+
+        if self.jitter {
+            delay.mul_f64(fastrand::f64())
+        } else {
+            delay
+        }
+    }
+
+    pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    pub(crate) fn is_retryable_error(error: &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout() || error.is_request()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(350))
+            .jitter(false);
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(350));
+        assert_eq!(policy.delay(100), Duration::from_millis(350));
+
+        let policy = policy.jitter(true);
+        assert!(policy.delay(3) <= Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(RetryPolicy::is_retryable_status(
+            StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(RetryPolicy::is_retryable_status(
+            StatusCode::TOO_MANY_REQUESTS
+        ));
+        assert!(!RetryPolicy::is_retryable_status(StatusCode::NOT_FOUND));
+    }
+}
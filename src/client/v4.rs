@@ -0,0 +1,367 @@
+//! OSS4-HMAC-SHA256 签名
+//!
+//! [aliyun docs](https://help.aliyun.com/zh/oss/developer-reference/recommend-to-use-signature-version-4)
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE, HOST},
+    Method,
+};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{
+    client::CONTENT_MD5,
+    credentials::Credentials,
+    error::OssError,
+    types::{append_query, encode_path, encode_query, hex, CanonicalizedResource},
+};
+
+const ALGORITHM: &str = "OSS4-HMAC-SHA256";
+const X_OSS_DATE: &str = "x-oss-date";
+const CONTENT_SHA256: &str = "x-oss-content-sha256";
+/// 不对请求体计算摘要，流式上传时也能签名
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// V4 签名所需的凭证、地域和额外参与签名的头信息
+pub(super) struct Signer<'a> {
+    pub credentials: &'a Credentials,
+    pub region: &'a str,
+    pub additional_headers: &'a [String],
+}
+
+impl Signer<'_> {
+    /// 计算 V4 签名，会向 `headers` 中加入 `x-oss-date`、`x-oss-content-sha256`，
+    /// 以及 `additional_headers` 中的 `host`，返回 `Authorization` 的值
+    pub fn authorization(
+        &self,
+        method: &Method,
+        url: &Url,
+        resource: &CanonicalizedResource,
+        headers: &mut HeaderMap,
+        now: DateTime<Utc>,
+    ) -> Result<String, OssError> {
+        let datetime = now.format("%Y%m%dT%H%M%SZ").to_string();
+        headers.insert(X_OSS_DATE, datetime.as_str().try_into()?);
+        headers.insert(CONTENT_SHA256, HeaderValue::from_static(UNSIGNED_PAYLOAD));
+
+        let additional = self.additional_headers(url, headers)?;
+        let signature = self.signature(method, url, resource, headers, &additional, now)?;
+
+        let mut value = format!(
+            "{} Credential={}/{}",
+            ALGORITHM,
+            self.credentials.key().as_str(),
+            self.scope(now)
+        );
+        if !additional.is_empty() {
+            value += ",AdditionalHeaders=";
+            value += &additional;
+        }
+        value += ",Signature=";
+        value += &signature;
+        Ok(value)
+    }
+
+    /// 生成预签名 URL，签名信息放在查询参数中，`expires` 为有效的秒数
+    pub fn presign(
+        &self,
+        method: &Method,
+        url: &mut Url,
+        resource: &CanonicalizedResource,
+        headers: &mut HeaderMap,
+        now: DateTime<Utc>,
+        expires: u64,
+    ) -> Result<(), OssError> {
+        let additional = self.additional_headers(url, headers)?;
+
+        let credential = format!("{}/{}", self.credentials.key().as_str(), self.scope(now));
+        append_query(url, "x-oss-signature-version", ALGORITHM);
+        append_query(url, "x-oss-credential", &credential);
+        append_query(url, X_OSS_DATE, &now.format("%Y%m%dT%H%M%SZ").to_string());
+        append_query(url, "x-oss-expires", &expires.to_string());
+        if !additional.is_empty() {
+            append_query(url, "x-oss-additional-headers", &additional);
+        }
+
+        let signature = self.signature(method, url, resource, headers, &additional, now)?;
+        append_query(url, "x-oss-signature", &signature);
+        Ok(())
+    }
+
+    fn scope(&self, now: DateTime<Utc>) -> String {
+        format!(
+            "{}/{}/oss/aliyun_v4_request",
+            now.format("%Y%m%d"),
+            self.region
+        )
+    }
+
+    /// 排序后以 `;` 连接的额外签名头，请求中没有的头信息不参与签名
+    fn additional_headers(&self, url: &Url, headers: &mut HeaderMap) -> Result<String, OssError> {
+        let mut additional: Vec<String> = self
+            .additional_headers
+            .iter()
+            .map(|h| h.to_ascii_lowercase())
+            .filter(|h| !is_default_signed(h))
+            .collect();
+        additional.sort();
+        additional.dedup();
+        if additional.iter().any(|h| h == HOST.as_str()) && !headers.contains_key(HOST) {
+            if let Some(host) = url.host_str() {
+                let host = match url.port() {
+                    Some(port) => format!("{}:{}", host, port),
+                    None => host.to_owned(),
+                };
+                headers.insert(HOST, host.try_into()?);
+            }
+        }
+        additional.retain(|h| headers.contains_key(h.as_str()));
+        Ok(additional.join(";"))
+    }
+
+    fn signature(
+        &self,
+        method: &Method,
+        url: &Url,
+        resource: &CanonicalizedResource,
+        headers: &HeaderMap,
+        additional: &str,
+        now: DateTime<Utc>,
+    ) -> Result<String, OssError> {
+        let canonical_request = {
+            let mut string = method.as_str().to_owned();
+            string += "\n";
+            string += &canonical_uri(resource);
+            string += "\n";
+            string += &canonical_query(url);
+            string += "\n";
+            string += &canonical_headers(headers, additional)?;
+            string += "\n";
+            string += additional;
+            string += "\n";
+            string += UNSIGNED_PAYLOAD;
+            string
+        };
+
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM,
+            now.format("%Y%m%dT%H%M%SZ"),
+            self.scope(now),
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        Ok(self
+            .credentials
+            .secret()
+            .encryption_v4(
+                &now.format("%Y%m%d").to_string(),
+                self.region,
+                string_to_sign.as_bytes(),
+            )
+            .unwrap())
+    }
+}
+
+/// 从 `bucket.oss-cn-hangzhou.aliyuncs.com` 这样的域名中取出地域 `cn-hangzhou`
+pub(super) fn region_from_host(host: &str) -> Option<&str> {
+    host.split('.')
+        .find_map(|label| label.strip_prefix("oss-"))
+        .map(|region| region.strip_suffix("-internal").unwrap_or(region))
+        .filter(|region| !region.is_empty())
+}
+
+/// 无论是否在 AdditionalHeaders 中，都会参与签名的头信息
+fn is_default_signed(name: &str) -> bool {
+    name == CONTENT_TYPE.as_str() || name == CONTENT_MD5 || name.starts_with("x-oss-")
+}
+
+/// `/bucket/object`，只取资源中 `?` 之前的部分，并进行 URL 编码
+fn canonical_uri(resource: &CanonicalizedResource) -> String {
+    encode_path(resource.path())
+}
+
+/// 请求中的全部查询参数，编码后按名称排序，没有值的参数只保留名称
+fn canonical_query(url: &Url) -> String {
+    let mut list: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (encode_query(&k), encode_query(&v)))
+        .collect();
+    list.sort();
+
+    list.into_iter()
+        .map(|(k, v)| {
+            if v.is_empty() {
+                k
+            } else {
+                format!("{}={}", k, v)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn canonical_headers(headers: &HeaderMap, additional: &str) -> Result<String, OssError> {
+    let additional: Vec<&str> = additional.split(';').collect();
+
+    let mut list = Vec::new();
+    for (name, value) in headers.iter() {
+        let name = name.as_str();
+        if is_default_signed(name) || additional.contains(&name) {
+            list.push((name, value.to_str()?.trim()));
+        }
+    }
+    list.sort();
+
+    let mut string = String::new();
+    for (name, value) in list {
+        string += name;
+        string += ":";
+        string += value;
+        string += "\n";
+    }
+    Ok(string)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use reqwest::{header::HeaderMap, Method};
+    use sha2::{Digest, Sha256};
+    use url::Url;
+
+    use super::{canonical_query, region_from_host, Signer};
+    use crate::{
+        credentials::Credentials,
+        types::{hex, CanonicalizedResource, Key, Secret},
+    };
+
+    #[test]
+    fn test_region_from_host() {
+        assert_eq!(
+            region_from_host("foo.oss-cn-hangzhou.aliyuncs.com"),
+            Some("cn-hangzhou")
+        );
+        assert_eq!(
+            region_from_host("oss-ap-southeast-1-internal.aliyuncs.com"),
+            Some("ap-southeast-1")
+        );
+        assert_eq!(region_from_host("127.0.0.1"), None);
+    }
+
+    #[test]
+    fn test_signing_key() {
+        let signature = Secret::new("bar")
+            .encryption_v4("20240102", "cn-hangzhou", b"string to sign")
+            .unwrap();
+        assert_eq!(
+            signature,
+            "37b3066eef85d375f0e68787e692d9e0ea9eba530c1edfacd4af817a0074df77"
+        );
+    }
+
+    #[test]
+    fn test_canonical_query() {
+        let url = Url::parse("https://foo.com/a.txt?uploadId=1&partNumber=2&acl&x=a%20b").unwrap();
+        assert_eq!(canonical_query(&url), "acl&partNumber=2&uploadId=1&x=a%20b");
+    }
+
+    /// 阿里云 OSS Go SDK V2（alibabacloud-oss-go-sdk-v2）V4 签名单元测试中的用例，
+    /// 签名值由官方 SDK 计算，不依赖本 crate 的实现
+    #[test]
+    fn test_authorization_sdk_vector() {
+        let credentials = Credentials::new(Key::new("ak"), Secret::new("sk"));
+        let url = Url::parse(
+            "http://bucket.oss-cn-hangzhou.aliyuncs.com/1234%2B-/123/1.txt\
+             ?param1=value1&%2Bparam1=value3&%7Cparam1=value4&%2Bparam2=&%7Cparam2=&param2=",
+        )
+        .unwrap();
+        let resource = CanonicalizedResource::new("/bucket/1234+-/123/1.txt".to_owned());
+        // 2023-12-16T16:20:57Z
+        let now = Utc.timestamp_opt(1702743657, 0).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-oss-head1", "value".parse().unwrap());
+        headers.insert("abc", "value".parse().unwrap());
+        headers.insert("zabc", "value".parse().unwrap());
+        headers.insert("xyz", "value".parse().unwrap());
+        headers.insert("content-type", "text/plain".parse().unwrap());
+
+        let signer = Signer {
+            credentials: &credentials,
+            region: "cn-hangzhou",
+            additional_headers: &[],
+        };
+        let value = signer
+            .authorization(&Method::PUT, &url, &resource, &mut headers, now)
+            .unwrap();
+        assert_eq!(
+            value,
+            "OSS4-HMAC-SHA256 Credential=ak/20231216/cn-hangzhou/oss/aliyun_v4_request,\
+             Signature=e21d18daa82167720f9b1047ae7e7f1ce7cb77a31e8203a7d5f4624fa0284afe"
+        );
+    }
+
+    #[test]
+    fn test_authorization() {
+        let credentials = Credentials::new(Key::new("foo"), Secret::new("bar"));
+        let url =
+            Url::parse("https://bucket.oss-cn-hangzhou.aliyuncs.com/dir/a%20b.txt?acl").unwrap();
+        let resource = CanonicalizedResource::new("/bucket/dir/a b.txt?acl".to_owned());
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", "text/plain".parse().unwrap());
+        headers.insert("x-oss-meta-a", " 1 ".parse().unwrap());
+        headers.insert("range", "bytes=0-9".parse().unwrap());
+
+        let additional_headers = [
+            "Host".to_owned(),
+            "range".to_owned(),
+            "x-oss-meta-a".to_owned(),
+        ];
+        let signer = Signer {
+            credentials: &credentials,
+            region: "cn-hangzhou",
+            additional_headers: &additional_headers,
+        };
+        let value = signer
+            .authorization(&Method::PUT, &url, &resource, &mut headers, now)
+            .unwrap();
+
+        assert_eq!(headers.get("x-oss-date").unwrap(), "20240102T030405Z");
+        assert_eq!(
+            headers.get("host").unwrap(),
+            "bucket.oss-cn-hangzhou.aliyuncs.com"
+        );
+
+        let canonical_request = "PUT\n\
+            /bucket/dir/a%20b.txt\n\
+            acl\n\
+            content-type:text/plain\n\
+            host:bucket.oss-cn-hangzhou.aliyuncs.com\n\
+            range:bytes=0-9\n\
+            x-oss-content-sha256:UNSIGNED-PAYLOAD\n\
+            x-oss-date:20240102T030405Z\n\
+            x-oss-meta-a:1\n\
+            \n\
+            host;range\n\
+            UNSIGNED-PAYLOAD";
+        let string_to_sign = format!(
+            "OSS4-HMAC-SHA256\n20240102T030405Z\n20240102/cn-hangzhou/oss/aliyun_v4_request\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signature = Secret::new("bar")
+            .encryption_v4("20240102", "cn-hangzhou", string_to_sign.as_bytes())
+            .unwrap();
+
+        assert_eq!(
+            value,
+            format!(
+                "OSS4-HMAC-SHA256 Credential=foo/20240102/cn-hangzhou/oss/aliyun_v4_request,\
+                 AdditionalHeaders=host;range,Signature={}",
+                signature
+            )
+        );
+    }
+}
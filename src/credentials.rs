@@ -0,0 +1,448 @@
+use std::{fmt, sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+
+use crate::{
+    error::OssError,
+    types::{Key, Secret},
+};
+
+mod ecs;
+mod oidc;
+mod profile;
+pub use ecs::EcsRamRoleProvider;
+pub use oidc::OidcProvider;
+pub use profile::ProfileProvider;
+
+/// 携带 STS 临时凭证时使用的请求头
+pub(crate) const SECURITY_TOKEN: &str = "x-oss-security-token";
+
+/// # 访问凭证
+///
+/// 长期凭证只有 AccessKeyId 和 AccessKeySecret；
+/// STS 临时凭证还带有 SecurityToken 和过期时间，请求时会通过 `x-oss-security-token` 发送并参与签名
+///
+/// ```
+/// # use chrono::{Duration, Utc};
+/// # use aliyun_oss_client::{Client, Credentials, Key, Secret};
+/// let credentials = Credentials::new(Key::new("STS.foo"), Secret::new("bar"))
+///     .with_security_token("CAIS...")
+///     .with_expiration(Utc::now() + Duration::hours(1));
+/// assert!(!credentials.is_expired());
+///
+/// let client = Client::from_credentials(credentials);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    key: Key,
+    secret: Secret,
+    security_token: Option<String>,
+    expiration: Option<DateTime<Utc>>,
+}
+
+impl Credentials {
+    pub fn new(key: Key, secret: Secret) -> Self {
+        Credentials {
+            key,
+            secret,
+            security_token: None,
+            expiration: None,
+        }
+    }
+
+    pub fn with_security_token<T: Into<String>>(mut self, token: T) -> Self {
+        self.security_token = Some(token.into());
+        self
+    }
+
+    pub fn with_expiration(mut self, expiration: DateTime<Utc>) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn secret(&self) -> &Secret {
+        &self.secret
+    }
+
+    pub fn security_token(&self) -> Option<&str> {
+        self.security_token.as_deref()
+    }
+
+    /// 过期时间，长期凭证返回 `None`
+    pub fn expiration(&self) -> Option<&DateTime<Utc>> {
+        self.expiration.as_ref()
+    }
+
+    /// 是否已过期，没有过期时间的凭证永远不会过期
+    pub fn is_expired(&self) -> bool {
+        self.expiration.is_some_and(|e| e <= Utc::now())
+    }
+}
+
+/// 不输出 secret 和 security token
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("key", &self.key)
+            .field("secret", &"******")
+            .field(
+                "security_token",
+                &self.security_token.as_ref().map(|_| "******"),
+            )
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+/// # 访问凭证的来源
+///
+/// [`Client`](crate::Client) 每次签名前通过它获取凭证，client 内部会缓存结果，
+/// 并在临时凭证过期前重新获取
+pub trait CredentialsProvider: fmt::Debug + Send + Sync {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>>;
+}
+
+/// 固定的凭证
+#[derive(Debug, Clone)]
+pub struct StaticProvider(Credentials);
+
+impl StaticProvider {
+    pub fn new(credentials: Credentials) -> Self {
+        StaticProvider(credentials)
+    }
+}
+
+impl CredentialsProvider for StaticProvider {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+        Box::pin(async move { Ok(self.0.clone()) })
+    }
+}
+
+/// 从环境变量中读取凭证
+///
+/// 使用 `ALIYUN_KEY_ID`、`ALIYUN_KEY_SECRET`，以及可选的 `ALIYUN_SECURITY_TOKEN`
+#[derive(Debug, Clone, Default)]
+pub struct EnvProvider;
+
+impl EnvProvider {
+    pub const SECURITY_TOKEN: &'static str = "ALIYUN_SECURITY_TOKEN";
+
+    pub fn new() -> Self {
+        EnvProvider
+    }
+
+    fn load() -> Result<Credentials, OssError> {
+        let key = Key::from_env()
+            .map_err(|_| OssError::Credentials("ALIYUN_KEY_ID is not set".to_owned()))?;
+        let secret = Secret::from_env()
+            .map_err(|_| OssError::Credentials("ALIYUN_KEY_SECRET is not set".to_owned()))?;
+
+        let credentials = Credentials::new(key, secret);
+        Ok(match std::env::var(Self::SECURITY_TOKEN) {
+            Ok(token) if !token.is_empty() => credentials.with_security_token(token),
+            _ => credentials,
+        })
+    }
+}
+
+impl CredentialsProvider for EnvProvider {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+        Box::pin(async { Self::load() })
+    }
+}
+
+/// # 依次尝试多个来源
+///
+/// 返回第一个成功获取的凭证，全部失败时返回包含各来源错误信息的 [`OssError::Credentials`]
+///
+/// ```
+/// # use aliyun_oss_client::credentials::{ChainProvider, EnvProvider, ProfileProvider};
+/// let chain = ChainProvider::new()
+///     .push(EnvProvider::new())
+///     .push(ProfileProvider::new().profile("dev"));
+/// ```
+#[derive(Debug, Default)]
+pub struct ChainProvider {
+    providers: Vec<Box<dyn CredentialsProvider>>,
+}
+
+impl ChainProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<P: CredentialsProvider + 'static>(mut self, provider: P) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// 默认的查找顺序：环境变量、ossutil 配置文件、OIDC（设置了相关环境变量时）、ECS 实例 RAM 角色
+    pub fn default_chain() -> Self {
+        let chain = Self::new()
+            .push(EnvProvider::new())
+            .push(ProfileProvider::new());
+        let chain = match OidcProvider::from_env() {
+            Ok(oidc) => chain.push(oidc),
+            Err(_) => chain,
+        };
+        chain.push(EcsRamRoleProvider::new())
+    }
+}
+
+impl CredentialsProvider for ChainProvider {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+        Box::pin(async move {
+            let mut errors = Vec::new();
+            for provider in self.providers.iter() {
+                match provider.provide().await {
+                    Ok(credentials) => return Ok(credentials),
+                    Err(e) => errors.push(e.to_string()),
+                }
+            }
+            Err(OssError::Credentials(format!(
+                "no credentials found in chain: [{}]",
+                errors.join("; ")
+            )))
+        })
+    }
+}
+
+/// # 缓存凭证
+///
+/// 凭证没有过期时间时一直使用缓存；有过期时间时，在过期前 `refresh_before` 重新获取。
+/// 同一时刻只有一个任务去获取新的凭证，获取失败时继续使用尚未过期的旧凭证
+#[derive(Debug)]
+pub struct CachedProvider<P> {
+    inner: P,
+    refresh_before: Duration,
+    cached: Mutex<Option<Credentials>>,
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl<P: CredentialsProvider> CachedProvider<P> {
+    /// 默认在过期前 5 分钟刷新
+    pub const DEFAULT_REFRESH_BEFORE: Duration = Duration::from_secs(5 * 60);
+
+    pub fn new(inner: P) -> Self {
+        CachedProvider {
+            inner,
+            refresh_before: Self::DEFAULT_REFRESH_BEFORE,
+            cached: Mutex::new(None),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn refresh_before(mut self, duration: Duration) -> Self {
+        self.refresh_before = duration;
+        self
+    }
+
+    fn cached(&self) -> Option<Credentials> {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        let refresh_before = chrono::Duration::from_std(self.refresh_before).ok()?;
+        cached.as_ref().and_then(|c| match c.expiration() {
+            Some(expiration) if *expiration - refresh_before <= Utc::now() => None,
+            _ => Some(c.clone()),
+        })
+    }
+
+    /// 即将过期但还可以使用的凭证
+    fn unexpired(&self) -> Option<Credentials> {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        cached.as_ref().filter(|c| !c.is_expired()).cloned()
+    }
+}
+
+impl<P: CredentialsProvider> CredentialsProvider for CachedProvider<P> {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+        Box::pin(async move {
+            if let Some(credentials) = self.cached() {
+                return Ok(credentials);
+            }
+
+            let _guard = self.refreshing.lock().await;
+            // 等待期间其他任务可能已经刷新了凭证
+            if let Some(credentials) = self.cached() {
+                return Ok(credentials);
+            }
+
+            match self.inner.provide().await {
+                Ok(credentials) => {
+                    *self.cached.lock().unwrap_or_else(|e| e.into_inner()) =
+                        Some(credentials.clone());
+                    Ok(credentials)
+                }
+                Err(e) => self.unexpired().ok_or(e),
+            }
+        })
+    }
+}
+
+/// 解析 STS 和 ECS 元数据服务返回的 json 中的凭证字段
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsCredentials {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: String,
+    expiration: String,
+}
+
+impl StsCredentials {
+    fn into_credentials(self) -> Result<Credentials, OssError> {
+        let expiration = DateTime::parse_from_rfc3339(&self.expiration)?.with_timezone(&Utc);
+        Ok(Credentials::new(
+            Key::new(self.access_key_id),
+            Secret::new(self.access_key_secret),
+        )
+        .with_security_token(self.security_token)
+        .with_expiration(expiration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use chrono::{Duration, Utc};
+    use futures_util::future::BoxFuture;
+
+    use super::{CachedProvider, ChainProvider, Credentials, CredentialsProvider, StaticProvider};
+    use crate::{
+        error::OssError,
+        types::{Key, Secret},
+    };
+
+    #[derive(Debug, Default)]
+    struct Counter {
+        calls: AtomicUsize,
+        expiration: Option<chrono::DateTime<Utc>>,
+        /// 第一次之后的调用都返回错误
+        fail_after_first: bool,
+        delay: Option<std::time::Duration>,
+    }
+
+    impl CredentialsProvider for Counter {
+        fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+            Box::pin(async move {
+                let n = self.calls.fetch_add(1, Ordering::SeqCst);
+                if let Some(delay) = self.delay {
+                    tokio::time::sleep(delay).await;
+                }
+                if self.fail_after_first && n > 0 {
+                    return Err(OssError::Credentials("unavailable".to_owned()));
+                }
+                let credentials =
+                    Credentials::new(Key::new(format!("key{}", n)), Secret::new("bar"));
+                Ok(match self.expiration {
+                    Some(expiration) => credentials.with_expiration(expiration),
+                    None => credentials,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_provider() {
+        let cached = CachedProvider::new(Counter::default());
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key0");
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key0");
+
+        // 即将过期的凭证每次都重新获取
+        let cached = CachedProvider::new(Counter {
+            expiration: Some(Utc::now() + Duration::minutes(1)),
+            ..Default::default()
+        });
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key0");
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key1");
+
+        let cached = CachedProvider::new(Counter {
+            expiration: Some(Utc::now() + Duration::minutes(1)),
+            ..Default::default()
+        })
+        .refresh_before(std::time::Duration::from_secs(10));
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key0");
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key0");
+    }
+
+    #[tokio::test]
+    async fn test_cached_provider_single_flight() {
+        let cached = CachedProvider::new(Counter {
+            delay: Some(std::time::Duration::from_millis(50)),
+            ..Default::default()
+        });
+
+        let list = futures_util::future::join_all((0..5).map(|_| cached.provide())).await;
+        assert!(list
+            .iter()
+            .all(|c| c.as_ref().unwrap().key().as_str() == "key0"));
+        assert_eq!(cached.inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cached_provider_refresh_failed() {
+        // 刷新失败时继续使用还没有过期的凭证
+        let cached = CachedProvider::new(Counter {
+            expiration: Some(Utc::now() + Duration::minutes(1)),
+            fail_after_first: true,
+            ..Default::default()
+        });
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key0");
+        assert_eq!(cached.provide().await.unwrap().key().as_str(), "key0");
+        assert_eq!(cached.inner.calls.load(Ordering::SeqCst), 2);
+
+        // 已经过期的凭证不再使用
+        let cached = CachedProvider::new(Counter {
+            expiration: Some(Utc::now() - Duration::seconds(1)),
+            fail_after_first: true,
+            ..Default::default()
+        });
+        assert!(cached.provide().await.is_ok());
+        assert!(cached.provide().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chain_provider() {
+        let err = ChainProvider::new()
+            .push(crate::credentials::ProfileProvider::new().path("/nonexistent/.ossutilconfig"))
+            .provide()
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("credentials error: no credentials found in chain: [credentials error: read /nonexistent/.ossutilconfig failed"));
+
+        let credentials = ChainProvider::new()
+            .push(crate::credentials::ProfileProvider::new().path("/nonexistent/.ossutilconfig"))
+            .push(StaticProvider::new(Credentials::new(
+                Key::new("foo"),
+                Secret::new("bar"),
+            )))
+            .provide()
+            .await
+            .unwrap();
+        assert_eq!(credentials.key().as_str(), "foo");
+    }
+
+    #[test]
+    fn test_credentials() {
+        let credentials = Credentials::new(Key::new("foo"), Secret::new("bar"));
+        assert!(!credentials.is_expired());
+        assert!(credentials.security_token().is_none());
+
+        let sts = credentials
+            .with_security_token("sts-token-value")
+            .with_expiration(Utc::now() - Duration::seconds(1));
+        assert!(sts.is_expired());
+        assert_eq!(sts.security_token(), Some("sts-token-value"));
+
+        let debug = format!("{:?}", sts);
+        assert!(!debug.contains("bar"));
+        assert!(!debug.contains("sts-token-value"));
+    }
+}
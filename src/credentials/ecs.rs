@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+
+use crate::{
+    credentials::{Credentials, CredentialsProvider, StsCredentials},
+    error::{check_response, OssError},
+};
+
+const SECURITY_CREDENTIALS_PATH: &str = "/latest/meta-data/ram/security-credentials/";
+
+/// # 通过 ECS 实例元数据服务获取 RAM 角色的临时凭证
+///
+/// 没有指定角色名称时，先从元数据服务查询实例绑定的角色
+#[derive(Debug, Clone)]
+pub struct EcsRamRoleProvider {
+    role_name: Option<String>,
+    endpoint: String,
+    http: reqwest::Client,
+}
+
+impl Default for EcsRamRoleProvider {
+    fn default() -> Self {
+        EcsRamRoleProvider {
+            role_name: None,
+            endpoint: Self::DEFAULT_ENDPOINT.to_owned(),
+            // 不在 ECS 上运行时元数据服务不可达，尽快失败以便尝试其他来源
+            http: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(1))
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl EcsRamRoleProvider {
+    pub const DEFAULT_ENDPOINT: &'static str = "http://100.100.100.200";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn role_name<N: Into<String>>(mut self, role_name: N) -> Self {
+        self.role_name = Some(role_name.into());
+        self
+    }
+
+    /// 元数据服务的地址，默认为 `http://100.100.100.200`
+    pub fn endpoint<E: Into<String>>(mut self, endpoint: E) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    async fn get(&self, path: &str) -> Result<String, OssError> {
+        let url = format!(
+            "{}{}{}",
+            self.endpoint.trim_end_matches('/'),
+            SECURITY_CREDENTIALS_PATH,
+            path
+        );
+        let response = self.http.get(url).send().await?;
+        Ok(check_response(response).await?.text().await?)
+    }
+
+    async fn load(&self) -> Result<Credentials, OssError> {
+        let role_name = match &self.role_name {
+            Some(role_name) => role_name.clone(),
+            None => self.get("").await?.trim().to_owned(),
+        };
+        if role_name.is_empty() {
+            return Err(OssError::Credentials(
+                "no ram role attached to the instance".to_owned(),
+            ));
+        }
+
+        let content = self.get(&role_name).await?;
+        let credentials: StsCredentials = serde_json::from_str(&content)?;
+        credentials.into_credentials()
+    }
+}
+
+impl CredentialsProvider for EcsRamRoleProvider {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+        Box::pin(async move {
+            self.load().await.map_err(|e| match e {
+                OssError::Credentials(message) => {
+                    OssError::Credentials(format!("ecs ram role: {}", message))
+                }
+                e => OssError::Credentials(format!("ecs ram role: {}", e)),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EcsRamRoleProvider;
+    use crate::{credentials::CredentialsProvider, test_server::serve};
+
+    #[tokio::test]
+    async fn test_provide() {
+        let (endpoint, handle) = serve(vec![
+            (200, "EcsRamRole".to_owned()),
+            (
+                200,
+                r#"{
+                    "AccessKeyId": "STS.foo",
+                    "AccessKeySecret": "bar",
+                    "Expiration": "2099-01-01T00:00:00Z",
+                    "SecurityToken": "CAIS-token",
+                    "LastUpdated": "2023-01-01T00:00:00Z",
+                    "Code": "Success"
+                }"#
+                .to_owned(),
+            ),
+        ]);
+
+        let credentials = EcsRamRoleProvider::new()
+            .endpoint(endpoint)
+            .provide()
+            .await
+            .unwrap();
+        assert_eq!(credentials.key().as_str(), "STS.foo");
+        assert_eq!(credentials.security_token(), Some("CAIS-token"));
+        assert!(!credentials.is_expired());
+
+        let requests: Vec<_> = handle.join().unwrap().into_iter().map(|r| r.line).collect();
+        assert_eq!(
+            requests,
+            [
+                "GET /latest/meta-data/ram/security-credentials/ HTTP/1.1",
+                "GET /latest/meta-data/ram/security-credentials/EcsRamRole HTTP/1.1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use std::{path::PathBuf, time::Duration};
+
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::{
+    bucket::Bucket,
+    credentials::{Credentials, CredentialsProvider, StsCredentials},
+    error::OssError,
+};
+
+/// # 通过 OIDC 令牌换取 RAM 角色的临时凭证（RRSA）
+///
+/// 调用 STS 的 AssumeRoleWithOIDC，该接口不需要签名。
+/// 令牌文件每次获取凭证时都会重新读取，以便使用轮换后的令牌
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    role_arn: String,
+    oidc_provider_arn: String,
+    token_file: PathBuf,
+    session_name: String,
+    duration_seconds: Option<u32>,
+    sts_endpoint: String,
+    http: reqwest::Client,
+}
+
+impl OidcProvider {
+    pub const DEFAULT_STS_ENDPOINT: &'static str = "https://sts.aliyuncs.com";
+
+    pub fn new<R, O, T>(role_arn: R, oidc_provider_arn: O, token_file: T) -> Self
+    where
+        R: Into<String>,
+        O: Into<String>,
+        T: Into<PathBuf>,
+    {
+        OidcProvider {
+            role_arn: role_arn.into(),
+            oidc_provider_arn: oidc_provider_arn.into(),
+            token_file: token_file.into(),
+            session_name: "aliyun-oss-client".to_owned(),
+            duration_seconds: None,
+            sts_endpoint: Self::DEFAULT_STS_ENDPOINT.to_owned(),
+            // STS 不可用时不能让签名一直等待
+            http: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(5))
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 读取 RRSA 注入的环境变量：`ALIBABA_CLOUD_ROLE_ARN`、`ALIBABA_CLOUD_OIDC_PROVIDER_ARN`、
+    /// `ALIBABA_CLOUD_OIDC_TOKEN_FILE`，以及可选的 `ALIBABA_CLOUD_ROLE_SESSION_NAME`
+    pub fn from_env() -> Result<Self, OssError> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| OssError::Credentials(format!("{} is not set", name)))
+        };
+
+        let provider = Self::new(
+            var("ALIBABA_CLOUD_ROLE_ARN")?,
+            var("ALIBABA_CLOUD_OIDC_PROVIDER_ARN")?,
+            var("ALIBABA_CLOUD_OIDC_TOKEN_FILE")?,
+        );
+        Ok(match var("ALIBABA_CLOUD_ROLE_SESSION_NAME") {
+            Ok(name) => provider.session_name(name),
+            Err(_) => provider,
+        })
+    }
+
+    pub fn session_name<N: Into<String>>(mut self, name: N) -> Self {
+        self.session_name = name.into();
+        self
+    }
+
+    /// 临时凭证的有效期，不设置时使用 STS 的默认值
+    pub fn duration_seconds(mut self, seconds: u32) -> Self {
+        self.duration_seconds = Some(seconds);
+        self
+    }
+
+    /// STS 服务的地址，默认为 `https://sts.aliyuncs.com`
+    pub fn sts_endpoint<E: Into<String>>(mut self, endpoint: E) -> Self {
+        self.sts_endpoint = endpoint.into();
+        self
+    }
+
+    async fn load(&self) -> Result<Credentials, OssError> {
+        let token = tokio::fs::read_to_string(&self.token_file).await?;
+
+        let mut url = url::Url::parse(&self.sts_endpoint)
+            .map_err(|_| OssError::Credentials("invalid sts endpoint".to_owned()))?;
+        url.query_pairs_mut()
+            .append_pair("Action", "AssumeRoleWithOIDC")
+            .append_pair("Format", "JSON")
+            .append_pair("Version", "2015-04-01")
+            .append_pair(
+                "Timestamp",
+                &Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            );
+
+        // 令牌放在请求体中，避免出现在访问日志和带有 URL 的错误信息里
+        let mut params = vec![
+            ("RoleArn", self.role_arn.clone()),
+            ("OIDCProviderArn", self.oidc_provider_arn.clone()),
+            ("OIDCToken", token.trim().to_owned()),
+            ("RoleSessionName", self.session_name.clone()),
+        ];
+        if let Some(seconds) = self.duration_seconds {
+            params.push(("DurationSeconds", seconds.to_string()));
+        }
+
+        let response = self.http.post(url).form(&params).send().await?;
+        let status = response.status();
+        let content = response.text().await?;
+        if !status.is_success() {
+            #[derive(Deserialize)]
+            #[serde(rename_all = "PascalCase")]
+            struct StsError {
+                code: String,
+                message: String,
+            }
+            return Err(match serde_json::from_str::<StsError>(&content) {
+                Ok(e) => OssError::Credentials(format!("{}: {}", e.code, e.message)),
+                // 非 json 格式的错误，尝试按 xml 解析错误码
+                Err(_) => OssError::Credentials(format!(
+                    "status {}: {}",
+                    status.as_u16(),
+                    Bucket::parse_item(&content, "Code").unwrap_or_default()
+                )),
+            });
+        }
+
+        #[derive(Deserialize)]
+        struct AssumeRoleResponse {
+            #[serde(rename = "Credentials")]
+            credentials: StsCredentials,
+        }
+        let res: AssumeRoleResponse = serde_json::from_str(&content)?;
+        res.credentials.into_credentials()
+    }
+}
+
+impl CredentialsProvider for OidcProvider {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+        Box::pin(async move {
+            self.load().await.map_err(|e| match e {
+                OssError::Credentials(message) => {
+                    OssError::Credentials(format!("oidc: {}", message))
+                }
+                e => OssError::Credentials(format!("oidc: {}", e)),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OidcProvider;
+    use crate::{credentials::CredentialsProvider, test_server::serve};
+
+    #[tokio::test]
+    async fn test_provide() {
+        let (endpoint, handle) = serve(vec![(
+            200,
+            r#"{
+                "RequestId": "3D57EAD2-8723-1F26-B69C-F8707D8B565D",
+                "Credentials": {
+                    "AccessKeyId": "STS.foo",
+                    "AccessKeySecret": "bar",
+                    "SecurityToken": "CAIS-token",
+                    "Expiration": "2099-01-01T00:00:00Z"
+                }
+            }"#
+            .to_owned(),
+        )]);
+
+        let token_file = std::env::temp_dir().join("aliyun-oss-oidc-token-test");
+        tokio::fs::write(&token_file, "eyJ-token\n").await.unwrap();
+
+        let credentials = OidcProvider::new(
+            "acs:ram::123:role/oss",
+            "acs:ram::123:oidc-provider/ack",
+            &token_file,
+        )
+        .sts_endpoint(endpoint)
+        .provide()
+        .await
+        .unwrap();
+        tokio::fs::remove_file(&token_file).await.unwrap();
+
+        assert_eq!(credentials.key().as_str(), "STS.foo");
+        assert_eq!(credentials.security_token(), Some("CAIS-token"));
+
+        let requests = handle.join().unwrap();
+        assert!(requests[0]
+            .line
+            .starts_with("POST /?Action=AssumeRoleWithOIDC&"));
+        // 令牌只出现在请求体中
+        assert!(!requests[0].line.contains("eyJ-token"));
+        assert!(!requests[0].line.contains("RoleArn"));
+        assert_eq!(
+            requests[0].header("content-type"),
+            Some("application/x-www-form-urlencoded")
+        );
+        let body = String::from_utf8_lossy(&requests[0].body);
+        assert!(body.contains("OIDCToken=eyJ-token&"));
+        assert!(body.contains("RoleArn=acs%3Aram%3A%3A123%3Arole%2Foss&"));
+    }
+
+    #[tokio::test]
+    async fn test_sts_error() {
+        let (endpoint, _handle) = serve(vec![(
+            400,
+            r#"{"RequestId": "1", "Code": "InvalidParameter.OIDCToken", "Message": "expired"}"#
+                .to_owned(),
+        )]);
+
+        let token_file = std::env::temp_dir().join("aliyun-oss-oidc-token-error-test");
+        tokio::fs::write(&token_file, "eyJ-token").await.unwrap();
+
+        let err = OidcProvider::new("role", "provider", &token_file)
+            .sts_endpoint(endpoint)
+            .provide()
+            .await
+            .unwrap_err();
+        tokio::fs::remove_file(&token_file).await.unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "credentials error: oidc: InvalidParameter.OIDCToken: expired"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+
+use futures_util::future::BoxFuture;
+
+use crate::{
+    credentials::{Credentials, CredentialsProvider},
+    error::OssError,
+    types::{Key, Secret},
+};
+
+/// # 从 ossutil 格式的配置文件中读取凭证
+///
+/// 默认读取 `~/.ossutilconfig` 中的 `[Credentials]`，可以通过 [`profile`](ProfileProvider::profile)
+/// 选择其他名称的配置：
+///
+/// ```ini
+/// [Credentials]
+/// endpoint=oss-cn-hangzhou.aliyuncs.com
+/// accessKeyID=foo
+/// accessKeySecret=bar
+///
+/// [dev]
+/// accessKeyID=STS.foo
+/// accessKeySecret=bar
+/// stsToken=CAIS...
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProfileProvider {
+    path: Option<PathBuf>,
+    profile: String,
+}
+
+impl Default for ProfileProvider {
+    fn default() -> Self {
+        ProfileProvider {
+            path: None,
+            profile: Self::DEFAULT_PROFILE.to_owned(),
+        }
+    }
+}
+
+impl ProfileProvider {
+    pub const DEFAULT_PROFILE: &'static str = "Credentials";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 配置文件的路径，默认为 `~/.ossutilconfig`
+    pub fn path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn profile<N: Into<String>>(mut self, profile: N) -> Self {
+        self.profile = profile.into();
+        self
+    }
+
+    fn config_path(&self) -> Result<PathBuf, OssError> {
+        if let Some(path) = &self.path {
+            return Ok(path.clone());
+        }
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .ok_or_else(|| OssError::Credentials("home directory not found".to_owned()))?;
+        Ok(PathBuf::from(home).join(".ossutilconfig"))
+    }
+
+    fn parse(&self, content: &str) -> Result<Credentials, OssError> {
+        let mut in_profile = false;
+        let mut key = None;
+        let mut secret = None;
+        let mut token = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_profile = name.trim() == self.profile;
+                continue;
+            }
+            if !in_profile {
+                continue;
+            }
+            if let Some((name, value)) = line.split_once('=') {
+                let value = value.trim().to_owned();
+                match name.trim() {
+                    "accessKeyID" => key = Some(value),
+                    "accessKeySecret" => secret = Some(value),
+                    "stsToken" => token = Some(value),
+                    _ => (),
+                }
+            }
+        }
+
+        let missing = |field| {
+            OssError::Credentials(format!("{} not found in profile [{}]", field, self.profile))
+        };
+        let credentials = Credentials::new(
+            Key::new(key.ok_or_else(|| missing("accessKeyID"))?),
+            Secret::new(secret.ok_or_else(|| missing("accessKeySecret"))?),
+        );
+
+        Ok(match token {
+            Some(token) if !token.is_empty() => credentials.with_security_token(token),
+            _ => credentials,
+        })
+    }
+}
+
+impl CredentialsProvider for ProfileProvider {
+    fn provide(&self) -> BoxFuture<'_, Result<Credentials, OssError>> {
+        Box::pin(async move {
+            let path = self.config_path()?;
+            let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                OssError::Credentials(format!("read {} failed: {}", path.display(), e))
+            })?;
+            self.parse(&content)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProfileProvider;
+    use crate::credentials::CredentialsProvider;
+
+    const CONFIG: &str = "
+[Credentials]
+language=EN
+endpoint=oss-cn-hangzhou.aliyuncs.com
+accessKeyID=foo
+accessKeySecret=bar
+
+[dev]
+accessKeyID = STS.foo
+accessKeySecret = bar2
+stsToken = CAIS-token
+";
+
+    #[test]
+    fn test_parse() {
+        let credentials = ProfileProvider::new().parse(CONFIG).unwrap();
+        assert_eq!(credentials.key().as_str(), "foo");
+        assert!(credentials.security_token().is_none());
+
+        let credentials = ProfileProvider::new().profile("dev").parse(CONFIG).unwrap();
+        assert_eq!(credentials.key().as_str(), "STS.foo");
+        assert_eq!(credentials.security_token(), Some("CAIS-token"));
+
+        assert!(ProfileProvider::new()
+            .profile("prod")
+            .parse(CONFIG)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_provide_from_file() {
+        let file = std::env::temp_dir().join("aliyun-oss-profile-test");
+        tokio::fs::write(&file, CONFIG).await.unwrap();
+
+        let provider = ProfileProvider::new().path(&file).profile("dev");
+        let credentials = provider.provide().await.unwrap();
+        tokio::fs::remove_file(&file).await.unwrap();
+
+        assert_eq!(credentials.key().as_str(), "STS.foo");
+        assert!(provider.provide().await.is_err());
+    }
+}
@@ -1,50 +1,381 @@
-use std::{
-    fmt::{self, Display},
-    num::ParseIntError,
-};
+use std::{fmt, num::ParseIntError};
 
-use reqwest::header::{InvalidHeaderValue, ToStrError};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::{
+    header::{InvalidHeaderValue, ToStrError},
+    Response, StatusCode,
+};
 use thiserror::Error;
 
+use crate::bucket::{xml_unescape, Bucket};
+
 #[derive(Debug, Error)]
 pub enum OssError {
+    #[error("reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
 
+    #[error("invalid header value: {0}")]
     HeaderValue(#[from] InvalidHeaderValue),
 
+    #[error("parse date error: {0}")]
     Chrono(#[from] chrono::ParseError),
 
+    #[error("header value to str error: {0}")]
     ToStrError(#[from] ToStrError),
 
+    #[error("not found CreationDate")]
     NoFoundCreationDate,
 
+    #[error("not found StorageClass")]
     NoFoundStorageClass,
 
+    #[error("not found DataRedundancyType")]
     NoFoundDataRedundancyType,
 
+    #[error("not found Content-Length")]
     NoFoundContentLength,
 
+    #[error("not found ETag")]
     NoFoundEtag,
 
+    #[error("not found Last-Modified")]
     NoFoundLastModified,
 
+    #[error("not found Key")]
+    NoFoundKey,
+
+    #[error("not found Content-Range")]
+    NoFoundContentRange,
+
+    #[error("parse int error: {0}")]
     ParseIntError(#[from] ParseIntError),
 
-    Upload(String),
+    /// OSS 返回的错误响应
+    #[error("{0}")]
+    Service(Box<ServiceError>),
+
+    #[error("invalid meta key")]
+    InvalidMetaKey,
+
+    /// 追加上传的位置与文件长度不一致，携带文件的当前长度
+    #[error("position not equal to length, the current length is {0}")]
+    PositionNotEqualToLength(u64),
+
+    #[error("not found x-oss-next-append-position")]
+    NoFoundNextAppendPosition,
+
+    #[error("not found UploadId")]
+    NoFoundUploadId,
 
-    Delete(String),
+    #[error("invalid part number")]
+    InvalidPartNumber,
 
+    #[error("invalid part size")]
+    InvalidPartSize,
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("the source file has been changed")]
+    SourceChanged,
+
+    /// 范围下载返回的内容与请求的范围不一致
+    #[error("the response does not match the requested range")]
+    UnexpectedRange,
+
+    /// PostObject 表单的文件名不满足策略中 `$key` 的条件
+    #[error("the key does not match the post policy")]
+    InvalidPostKey,
+
+    /// 按前缀批量操作时传入了空的前缀，删除全部文件需要使用 `delete_all`
+    #[error("prefix can not be empty")]
+    EmptyPrefix,
+
+    /// 重命名的目标与源相同，或者两个目录互相包含
+    #[error("the rename target overlaps with the source")]
+    InvalidRenameTarget,
+
+    #[error("not found bucket")]
     NoFoundBucket,
 
+    #[error("parse xml error: {0}")]
     ParseXml(#[from] serde_xml_rs::Error),
 
+    #[error("invalid endpoint")]
     InvalidEndPoint,
 
+    #[error("invalid bucket")]
     InvalidBucket,
+
+    /// 创建 client 时的配置有误
+    #[error("invalid client config: {0}")]
+    InvalidConfig(String),
+
+    /// 获取访问凭证失败
+    #[error("credentials error: {0}")]
+    Credentials(String),
+}
+
+impl OssError {
+    /// 读取失败响应的内容，转换为 [`OssError::Service`]
+    pub(crate) async fn from_response(response: Response) -> OssError {
+        let status = response.status();
+        let request_id = header_str(&response, "x-oss-request-id");
+        let ec = header_str(&response, "x-oss-ec");
+        // HEAD 之类没有响应体的请求，错误信息以 base64 编码放在 x-oss-err 头中
+        let err_header = header_str(&response, "x-oss-err")
+            .and_then(|e| STANDARD.decode(e).ok())
+            .and_then(|e| String::from_utf8(e).ok());
+
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return e.into(),
+        };
+        let xml = match err_header {
+            Some(err) if body.is_empty() => err,
+            _ => body,
+        };
+
+        let mut error = ServiceError::parse_xml(status, &xml);
+        if error.request_id.is_empty() {
+            error.request_id = request_id.unwrap_or_default();
+        }
+        if error.ec.is_none() {
+            error.ec = ec;
+        }
+        OssError::Service(Box::new(error))
+    }
+
+    /// OSS 返回的错误码，其他错误返回 `None`
+    pub fn service_code(&self) -> Option<&ServiceErrorCode> {
+        match self {
+            OssError::Service(e) => Some(e.code()),
+            _ => None,
+        }
+    }
+}
+
+/// 检查响应的状态码，非 2xx 的响应转换为 [`OssError::Service`]，
+/// 调用方只需要处理成功的响应
+pub(crate) async fn check_response(response: Response) -> Result<Response, OssError> {
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(OssError::from_response(response).await)
+    }
+}
+
+fn header_str(response: &Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_owned())
+}
+
+/// # OSS 返回的错误信息
+///
+/// 对应响应体中的 `<Error>`，同时记录 HTTP 状态码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceError {
+    status: StatusCode,
+    code: ServiceErrorCode,
+    message: String,
+    request_id: String,
+    host_id: String,
+    ec: Option<String>,
+}
+
+impl ServiceError {
+    /// 解析 `<Error>` 格式的 xml，缺少的字段为空字符串
+    ///
+    /// ```
+    /// # use aliyun_oss_client::error::{ServiceError, ServiceErrorCode};
+    /// # use reqwest::StatusCode;
+    /// let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+    /// <Error>
+    ///   <Code>NoSuchKey</Code>
+    ///   <Message>The specified key does not exist.</Message>
+    ///   <RequestId>5C3D9175B6FC201293AD****</RequestId>
+    ///   <HostId>foo.oss-cn-qingdao.aliyuncs.com</HostId>
+    ///   <EC>0026-00000001</EC>
+    /// </Error>"#;
+    /// let error = ServiceError::parse_xml(StatusCode::NOT_FOUND, xml);
+    /// assert_eq!(error.code(), &ServiceErrorCode::NoSuchKey);
+    /// assert_eq!(error.request_id(), "5C3D9175B6FC201293AD****");
+    /// assert_eq!(error.ec(), Some("0026-00000001"));
+    /// ```
+    pub fn parse_xml(status: StatusCode, xml: &str) -> ServiceError {
+        let item = |field| Bucket::parse_item(xml, field).map(xml_unescape);
+        ServiceError {
+            status,
+            code: item("Code").unwrap_or_default().as_str().into(),
+            message: item("Message").unwrap_or_default(),
+            request_id: item("RequestId").unwrap_or_default(),
+            host_id: item("HostId").unwrap_or_default(),
+            ec: item("EC"),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn code(&self) -> &ServiceErrorCode {
+        &self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    /// 错误的详细编号，可在 OSS 文档中查询具体原因
+    pub fn ec(&self) -> Option<&str> {
+        self.ec.as_deref()
+    }
 }
 
-impl Display for OssError {
+impl fmt::Display for ServiceError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        "oss error".fmt(f)
+        write!(
+            f,
+            "oss service error {} {}",
+            self.status.as_u16(),
+            self.code
+        )?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        if !self.request_id.is_empty() {
+            write!(f, " (request id: {})", self.request_id)?;
+        }
+        Ok(())
+    }
+}
+
+/// # OSS 的错误码
+///
+/// 常用的错误码可以直接匹配，其他的保存在 `Other` 中
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceErrorCode {
+    NoSuchKey,
+    NoSuchBucket,
+    NoSuchUpload,
+    AccessDenied,
+    SignatureDoesNotMatch,
+    InvalidAccessKeyId,
+    PositionNotEqualToLength,
+    Other(String),
+}
+
+impl ServiceErrorCode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ServiceErrorCode::NoSuchKey => "NoSuchKey",
+            ServiceErrorCode::NoSuchBucket => "NoSuchBucket",
+            ServiceErrorCode::NoSuchUpload => "NoSuchUpload",
+            ServiceErrorCode::AccessDenied => "AccessDenied",
+            ServiceErrorCode::SignatureDoesNotMatch => "SignatureDoesNotMatch",
+            ServiceErrorCode::InvalidAccessKeyId => "InvalidAccessKeyId",
+            ServiceErrorCode::PositionNotEqualToLength => "PositionNotEqualToLength",
+            ServiceErrorCode::Other(code) => code,
+        }
+    }
+}
+
+impl From<&str> for ServiceErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "NoSuchKey" => ServiceErrorCode::NoSuchKey,
+            "NoSuchBucket" => ServiceErrorCode::NoSuchBucket,
+            "NoSuchUpload" => ServiceErrorCode::NoSuchUpload,
+            "AccessDenied" => ServiceErrorCode::AccessDenied,
+            "SignatureDoesNotMatch" => ServiceErrorCode::SignatureDoesNotMatch,
+            "InvalidAccessKeyId" => ServiceErrorCode::InvalidAccessKeyId,
+            "PositionNotEqualToLength" => ServiceErrorCode::PositionNotEqualToLength,
+            other => ServiceErrorCode::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for ServiceErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::{check_response, OssError, ServiceError, ServiceErrorCode};
+
+    #[test]
+    fn test_service_error() {
+        let xml = r#"<Error>
+            <Code>SignatureDoesNotMatch</Code>
+            <Message>The request signature we calculated does not match the signature you provided.</Message>
+            <RequestId>5C3D9175B6FC201293AD****</RequestId>
+            <HostId>foo.oss-cn-qingdao.aliyuncs.com</HostId>
+        </Error>"#;
+
+        let error = OssError::Service(Box::new(ServiceError::parse_xml(
+            StatusCode::FORBIDDEN,
+            xml,
+        )));
+        assert_eq!(
+            error.service_code(),
+            Some(&ServiceErrorCode::SignatureDoesNotMatch)
+        );
+        assert_eq!(
+            error.to_string(),
+            "oss service error 403 SignatureDoesNotMatch: The request signature we calculated does not match the signature you provided. (request id: 5C3D9175B6FC201293AD****)"
+        );
+
+        let error = ServiceError::parse_xml(
+            StatusCode::BAD_REQUEST,
+            "<Error><Code>InvalidArgument</Code></Error>",
+        );
+        assert_eq!(
+            error.code(),
+            &ServiceErrorCode::Other("InvalidArgument".to_owned())
+        );
+        assert_eq!(error.ec(), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_response() {
+        let ok = http::Response::builder()
+            .status(200)
+            .body("content")
+            .unwrap();
+        let response = check_response(ok.into()).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "content");
+
+        let not_found = http::Response::builder()
+            .status(404)
+            .header("x-oss-request-id", "5C3D9175B6FC201293AD****")
+            .body("<Error><Code>NoSuchKey</Code></Error>")
+            .unwrap();
+        let error = check_response(not_found.into()).await.unwrap_err();
+        match error {
+            OssError::Service(e) => {
+                assert_eq!(e.status(), StatusCode::NOT_FOUND);
+                assert_eq!(e.code(), &ServiceErrorCode::NoSuchKey);
+                assert_eq!(e.request_id(), "5C3D9175B6FC201293AD****");
+            }
+            e => panic!("unexpected error: {e}"),
+        }
     }
 }
@@ -1,13 +1,20 @@
 pub mod bucket;
 pub mod client;
+pub mod credentials;
 pub mod error;
 pub mod object;
 pub mod types;
 
+#[cfg(test)]
+mod test_server;
+
 pub use bucket::Bucket;
 pub use bucket::BucketInfo;
+pub use bucket::RenameReport;
 pub use client::Client;
+pub use credentials::Credentials;
 pub use object::Object;
 pub use object::ObjectInfo;
 pub use object::Objects;
+pub use object::Owner;
 pub use types::{EndPoint, Key, Secret};
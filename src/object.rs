@@ -1,24 +1,77 @@
+use std::path::Path;
+
+use bytes::Bytes;
 use chrono::{DateTime, Utc};
-use reqwest::{header::CONTENT_LENGTH, Method};
+use futures_util::{Stream, TryStreamExt};
+use reqwest::{header::CONTENT_LENGTH, Body, Method};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
 use url::Url;
 
 use crate::{
+    bucket::xml_unescape,
     client::Client,
-    error::OssError,
-    types::{CanonicalizedResource, ObjectQuery},
+    error::{check_response, OssError},
+    types::{CanonicalizedResource, ObjectQuery, StorageClass},
     Bucket,
 };
 
+mod append;
+mod copy;
+mod download;
+mod multipart;
+mod parallel;
+mod presign;
+mod resumable;
+mod upload;
+pub use append::Appender;
+pub use copy::{CopyOptions, MetadataDirective, MULTIPART_COPY_THRESHOLD};
+pub use download::{ByteRange, ContentRange, DownloadOptions, DownloadResponse};
+pub use multipart::{MultipartUpload, UploadedPart, MAX_PART_NUMBER, MIN_PART_SIZE};
+pub use parallel::ParallelDownload;
+pub use presign::PresignOptions;
+pub use resumable::UploadCheckpoint;
+pub use upload::UploadOptions;
+
 #[derive(Debug)]
 pub struct Objects {
-    //bucket: Bucket,
+    bucket: Option<Bucket>,
     list: Vec<Object>,
+    common_prefixes: Vec<String>,
     next_token: Option<String>,
 }
 
 impl Objects {
     pub fn new(list: Vec<Object>, next_token: Option<String>) -> Objects {
-        Objects { list, next_token }
+        Objects {
+            bucket: None,
+            list,
+            common_prefixes: Vec::new(),
+            next_token,
+        }
+    }
+
+    pub(crate) fn with_common_prefixes(mut self, common_prefixes: Vec<String>) -> Objects {
+        self.common_prefixes = common_prefixes;
+        self
+    }
+
+    /// 使用 `delimiter` 查询时，当前层级下的子目录（例如 `fun/movie/`）
+    ///
+    /// 子目录不会出现在 [`get_vec`](Objects::get_vec) 返回的文件列表中
+    pub fn common_prefixes(&self) -> &[String] {
+        &self.common_prefixes
+    }
+
+    /// 记录返回该列表的 bucket，翻页时会使用它
+    pub(crate) fn with_bucket(mut self, bucket: Bucket) -> Objects {
+        self.bucket = Some(bucket);
+        self
+    }
+
+    /// 返回该列表的 bucket
+    pub fn bucket(&self) -> Option<&Bucket> {
+        self.bucket.as_ref()
     }
 
     pub fn next_token(&self) -> Option<&String> {
@@ -37,6 +90,11 @@ impl Objects {
         &self.list
     }
 
+    pub fn into_vec(self) -> Vec<Object> {
+        self.list
+    }
+
+    /// 获取下一页，优先使用返回当前列表的 bucket，其次才是 client 中的默认 bucket
     pub async fn next_list(
         self,
         query: &ObjectQuery,
@@ -46,21 +104,142 @@ impl Objects {
         if let Some(token) = self.next_token {
             q.insert(ObjectQuery::CONTINUATION_TOKEN, token);
         }
-        match client.bucket() {
+        match self.bucket.as_ref().or(client.bucket()) {
             Some(bucket) => bucket.get_objects(&q, client).await,
             None => Err(OssError::NoFoundBucket),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// # OSS 中的文件
+///
+/// 通过 [`Object::new`] 创建时只有路径；由列表接口返回时，还会带有列表中的元信息。
+/// 两个 `Object` 是否相等以及排序都只比较路径
+#[derive(Debug, Clone)]
 pub struct Object {
     path: String,
+    last_modified: Option<DateTime<Utc>>,
+    etag: Option<String>,
+    size: Option<u64>,
+    storage_class: Option<StorageClass>,
+    object_type: Option<String>,
+    owner: Option<Owner>,
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Eq for Object {}
+
+impl PartialOrd for Object {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Object {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.path.cmp(&other.path)
+    }
+}
+
+/// 文件的拥有者，列表查询时需要设置 `fetch-owner=true` 才会返回
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Owner {
+    id: String,
+    display_name: String,
+}
+
+impl Owner {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
 }
 
 impl Object {
     pub fn new<P: Into<String>>(path: P) -> Object {
-        Object { path: path.into() }
+        Object {
+            path: path.into(),
+            last_modified: None,
+            etag: None,
+            size: None,
+            storage_class: None,
+            object_type: None,
+            owner: None,
+        }
+    }
+
+    /// 解析 ListObjectsV2 结果中的一个 `<Contents>` 块
+    pub(crate) fn from_list_xml(xml: &str) -> Result<Object, OssError> {
+        let path = Bucket::parse_item(xml, "Key").ok_or(OssError::NoFoundKey)?;
+        let mut object = Object::new(xml_unescape(path));
+
+        if let Some(time) = Bucket::parse_item(xml, "LastModified") {
+            object.last_modified = Some(DateTime::parse_from_rfc3339(time)?.with_timezone(&Utc));
+        }
+        object.etag = Bucket::parse_item(xml, "ETag").map(xml_unescape);
+        if let Some(size) = Bucket::parse_item(xml, "Size") {
+            object.size = Some(size.parse()?);
+        }
+        object.storage_class = Bucket::parse_item(xml, "StorageClass").and_then(StorageClass::new);
+        object.object_type = Bucket::parse_item(xml, "Type").map(|s| s.to_owned());
+        object.owner = Bucket::parse_blocks(xml, "Owner")
+            .first()
+            .map(|block| Owner {
+                id: Bucket::parse_item(block, "ID")
+                    .unwrap_or_default()
+                    .to_owned(),
+                display_name: xml_unescape(
+                    Bucket::parse_item(block, "DisplayName").unwrap_or_default(),
+                ),
+            });
+
+        Ok(object)
+    }
+
+    pub fn last_modified(&self) -> Option<&DateTime<Utc>> {
+        self.last_modified.as_ref()
+    }
+
+    /// 带双引号的 ETag，与 [`ObjectInfo::etag`] 的格式一致
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// 存储类型，如 `Standard`、`IA`、`Archive`，无法识别的类型为 `None`
+    pub fn storage_class(&self) -> Option<&StorageClass> {
+        self.storage_class.as_ref()
+    }
+
+    /// 文件类型，如 `Normal`、`Multipart`、`Appendable`
+    pub fn object_type(&self) -> Option<&str> {
+        self.object_type.as_deref()
+    }
+
+    pub fn owner(&self) -> Option<&Owner> {
+        self.owner.as_ref()
+    }
+
+    /// 由列表结果中的元信息组成 [`ObjectInfo`]，不需要再调用 [`Object::get_info`]
+    ///
+    /// 通过 [`Object::new`] 创建的文件没有这些信息，返回 `None`
+    pub fn info(&self) -> Option<ObjectInfo> {
+        Some(ObjectInfo::new(
+            self.last_modified?,
+            self.etag.clone()?,
+            self.size?,
+        ))
     }
 
     /// 确认文件是否在目录里面
@@ -147,13 +326,8 @@ impl Object {
         let resource =
             CanonicalizedResource::new(format!("/{}/{}?objectMeta", bucket.as_str(), self.path));
 
-        let header_map = client.authorization(method, resource)?;
-
-        let response = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?;
+        let response = client.request(method, url, resource).send().await?;
+        let response = check_response(response).await?;
 
         let headers = response.headers();
 
@@ -176,66 +350,180 @@ impl Object {
     }
 
     pub async fn upload(&self, content: Vec<u8>, client: &Client) -> Result<(), OssError> {
+        self.upload_with(content, &UploadOptions::new(), client)
+            .await
+    }
+
+    /// 上传文件，可以指定 `Content-Type` 等参数
+    pub async fn upload_with(
+        &self,
+        content: Vec<u8>,
+        options: &UploadOptions,
+        client: &Client,
+    ) -> Result<(), OssError> {
         let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
         let url = self.to_url(bucket);
         let method = Method::PUT;
         let resource = CanonicalizedResource::new(format!("/{}/{}", bucket.as_str(), self.path));
 
-        let header_map = client.authorization(method, resource)?;
-
-        let response = reqwest::Client::new()
-            .put(url)
-            .headers(header_map)
+        let response = client
+            .request(method, url, resource)
+            .headers(options.to_headers()?)
             .body(content)
             .send()
             .await?;
 
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            let body = response.text().await?;
-            Err(OssError::Upload(body))
-        }
+        check_response(response).await?;
+        Ok(())
     }
-    pub async fn download(&self, client: &Client) -> Result<Vec<u8>, OssError> {
+
+    /// 从 `AsyncRead` 中读取 `len` 个字节并上传，内容以流的形式发送，不会全部读入内存
+    pub async fn upload_reader<R>(
+        &self,
+        reader: R,
+        len: u64,
+        client: &Client,
+    ) -> Result<(), OssError>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        self.upload_reader_with(reader, len, &UploadOptions::new(), client)
+            .await
+    }
+
+    /// 与 [`upload_reader`](Object::upload_reader) 相同，可以指定 `Content-Type` 等参数
+    pub async fn upload_reader_with<R>(
+        &self,
+        reader: R,
+        len: u64,
+        options: &UploadOptions,
+        client: &Client,
+    ) -> Result<(), OssError>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
         let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
         let url = self.to_url(bucket);
-        let method = Method::GET;
+        let method = Method::PUT;
         let resource = CanonicalizedResource::new(format!("/{}/{}", bucket.as_str(), self.path));
 
-        let header_map = client.authorization(method, resource)?;
+        let mut headers = options.to_headers()?;
+        headers.insert(CONTENT_LENGTH, len.into());
 
-        let response = reqwest::Client::new()
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .bytes()
+        let body = Body::wrap_stream(ReaderStream::new(reader.take(len)));
+
+        let response = client
+            .request(method, url, resource)
+            .headers(headers)
+            .send_stream(body)
             .await?;
 
-        Ok(response.into())
+        check_response(response).await?;
+        Ok(())
     }
 
-    pub async fn delete(&self, client: &Client) -> Result<(), OssError> {
+    /// 上传已打开的文件，从文件当前的位置读取到结尾
+    pub async fn upload_from_file(
+        &self,
+        mut file: tokio::fs::File,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let len = file.metadata().await?.len();
+        let position = file.stream_position().await?;
+
+        self.upload_reader(file, len.saturating_sub(position), client)
+            .await
+    }
+
+    /// 上传本地路径对应的文件
+    pub async fn upload_file<P: AsRef<Path>>(
+        &self,
+        file: P,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let file = tokio::fs::File::open(file).await?;
+        self.upload_from_file(file, client).await
+    }
+
+    pub async fn download(&self, client: &Client) -> Result<Vec<u8>, OssError> {
         let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
         let url = self.to_url(bucket);
-        let method = Method::DELETE;
+        let method = Method::GET;
         let resource = CanonicalizedResource::new(format!("/{}/{}", bucket.as_str(), self.path));
 
-        let header_map = client.authorization(method, resource)?;
+        let response = client.request(method, url, resource).send().await?;
+        let content = check_response(response).await?.bytes().await?;
 
-        let response = reqwest::Client::new()
-            .delete(url)
-            .headers(header_map)
-            .send()
-            .await?;
+        Ok(content.into())
+    }
+
+    /// 以字节流的形式下载文件，不会把整个文件读入内存
+    ///
+    /// ```no_run
+    /// # use aliyun_oss_client::{Client, Object};
+    /// use futures_util::TryStreamExt;
+    /// # async fn run(client: &Client) -> Result<(), aliyun_oss_client::error::OssError> {
+    /// let object = Object::new("big.zip");
+    /// let mut stream = object.download_stream(client).await?;
+    /// while let Some(chunk) = stream.try_next().await? {
+    ///     println!("{}", chunk.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_stream(
+        &self,
+        client: &Client,
+    ) -> Result<impl Stream<Item = Result<Bytes, OssError>>, OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        let url = self.to_url(bucket);
+        let method = Method::GET;
+        let resource = CanonicalizedResource::new(format!("/{}/{}", bucket.as_str(), self.path));
+
+        let response = client.request(method, url, resource).send().await?;
 
-        if response.status().is_success() {
+        let response = check_response(response).await?;
+
+        Ok(response.bytes_stream().map_err(OssError::from))
+    }
+
+    /// 下载文件并直接写入本地路径
+    ///
+    /// 下载中途失败时会删除已经写入的部分文件
+    pub async fn download_to_file<P: AsRef<Path>>(
+        &self,
+        file: P,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let path = file.as_ref();
+        let mut stream = self.download_stream(client).await?;
+        let mut file = tokio::fs::File::create(path).await?;
+
+        let res = async {
+            while let Some(chunk) = stream.try_next().await? {
+                file.write_all(&chunk).await?;
+            }
+            file.flush().await?;
             Ok(())
-        } else {
-            let body = response.text().await?;
-            Err(OssError::Delete(body))
         }
+        .await;
+        if res.is_err() {
+            drop(file);
+            let _ = tokio::fs::remove_file(path).await;
+        }
+        res
+    }
+
+    pub async fn delete(&self, client: &Client) -> Result<(), OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        let url = self.to_url(bucket);
+        let method = Method::DELETE;
+        let resource = CanonicalizedResource::new(format!("/{}/{}", bucket.as_str(), self.path));
+
+        let response = client.request(method, url, resource).send().await?;
+
+        check_response(response).await?;
+        Ok(())
     }
 }
 
@@ -269,11 +557,14 @@ impl ObjectInfo {
 
 #[cfg(test)]
 mod tests {
-    use super::Object;
+    use futures_util::TryStreamExt;
+
+    use super::{Object, UploadOptions};
     use crate::{
         bucket::Bucket,
         client::{init_client, Client},
-        types::{EndPoint, ObjectQuery},
+        test_server::{self, serve, serve_raw},
+        types::{EndPoint, ObjectQuery, StorageClass},
     };
 
     fn set_client() -> Client {
@@ -283,6 +574,155 @@ mod tests {
         client
     }
 
+    #[tokio::test]
+    async fn test_download_stream() {
+        let (server, handle) = serve(vec![(200, "hello world".to_owned())]);
+
+        let stream = Object::new("dir/abc.txt")
+            .download_stream(&test_server::client(&server))
+            .await
+            .unwrap();
+        let chunks: Vec<_> = stream.try_collect().await.unwrap();
+        assert_eq!(chunks.concat(), b"hello world");
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "GET /dir/abc.txt HTTP/1.1");
+        assert!(requests[0].header("range").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_download_to_file() {
+        let (server, _handle) = serve(vec![(200, "hello world".to_owned())]);
+        let file = std::env::temp_dir().join("aliyun-oss-download-to-file-test");
+
+        Object::new("abc.txt")
+            .download_to_file(&file, &test_server::client(&server))
+            .await
+            .unwrap();
+        assert_eq!(tokio::fs::read(&file).await.unwrap(), b"hello world");
+        tokio::fs::remove_file(&file).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_to_file_cleanup() {
+        // 声明了 100 个字节，只返回 5 个字节后断开
+        let (server, _handle) = serve_raw(vec![
+            "HTTP/1.1 200 OK\r\ncontent-length: 100\r\nconnection: close\r\n\r\nhello".to_owned(),
+        ]);
+        let file = std::env::temp_dir().join("aliyun-oss-download-cleanup-test");
+
+        let res = Object::new("abc.txt")
+            .download_to_file(&file, &test_server::client(&server))
+            .await;
+        assert!(res.is_err());
+        assert!(!file.exists());
+
+        // 错误的状态码不会创建文件
+        let (server, _handle) = serve(vec![(404, String::new())]);
+        let res = Object::new("abc.txt")
+            .download_to_file(&file, &test_server::client(&server))
+            .await;
+        assert!(res.is_err());
+        assert!(!file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_upload_reader() {
+        let (server, handle) = serve(vec![(200, String::new())]);
+
+        // 只读取 len 个字节
+        let reader = std::io::Cursor::new(b"hello world".to_vec());
+        Object::new("abc.txt")
+            .upload_reader(reader, 5, &test_server::client(&server))
+            .await
+            .unwrap();
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "PUT /abc.txt HTTP/1.1");
+        assert_eq!(requests[0].header("content-length"), Some("5"));
+        assert_eq!(requests[0].body, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_upload_content_type() {
+        let (server, handle) = serve(vec![(200, String::new()), (200, String::new())]);
+        let client = test_server::client(&server);
+
+        let object = Object::new("abc.txt");
+        object.upload("aaa".into(), &client).await.unwrap();
+        let options = UploadOptions::new().content_type("text/plain");
+        object
+            .upload_with("aaa".into(), &options, &client)
+            .await
+            .unwrap();
+
+        let requests = handle.join().unwrap();
+        assert!(requests[0].header("content-type").is_none());
+        assert_eq!(requests[1].header("content-type"), Some("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_from_file() {
+        use tokio::io::AsyncSeekExt;
+
+        let path = std::env::temp_dir().join("aliyun-oss-upload-from-file-test");
+        tokio::fs::write(&path, "hello world").await.unwrap();
+
+        // 从文件当前的位置开始上传
+        let (server, handle) = serve(vec![(200, String::new())]);
+        let mut file = tokio::fs::File::open(&path).await.unwrap();
+        file.seek(std::io::SeekFrom::Start(6)).await.unwrap();
+        Object::new("abc.txt")
+            .upload_from_file(file, &test_server::client(&server))
+            .await
+            .unwrap();
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].header("content-length"), Some("5"));
+        assert_eq!(requests[0].body, b"world");
+
+        let (server, handle) = serve(vec![(200, String::new())]);
+        Object::new("abc.txt")
+            .upload_file(&path, &test_server::client(&server))
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].header("content-length"), Some("11"));
+        assert_eq!(requests[0].body, b"hello world");
+    }
+
+    #[test]
+    fn test_from_list_xml() {
+        let xml = r#"
+            <Key>fun/test.jpg</Key>
+            <LastModified>2022-06-26T09:53:21.000Z</LastModified>
+            <ETag>&quot;F75A15996D0857B16FA31A3B16624C26&quot;</ETag>
+            <Type>Normal</Type>
+            <Size>18027</Size>
+            <StorageClass>IA</StorageClass>
+            <Owner>
+                <ID>1501093843001409</ID>
+                <DisplayName>1501093843001409</DisplayName>
+            </Owner>"#;
+
+        let object = Object::from_list_xml(xml).unwrap();
+        assert_eq!(object.get_path(), "fun/test.jpg");
+        assert_eq!(object.etag(), Some("\"F75A15996D0857B16FA31A3B16624C26\""));
+        assert_eq!(object.object_type(), Some("Normal"));
+        assert_eq!(object.storage_class(), Some(&StorageClass::IA));
+        assert_eq!(object.owner().unwrap().id(), "1501093843001409");
+
+        let info = object.info().unwrap();
+        assert_eq!(info.size(), 18027);
+        assert_eq!(
+            info.last_modified().to_rfc3339(),
+            "2022-06-26T09:53:21+00:00"
+        );
+
+        assert_eq!(object, Object::new("fun/test.jpg"));
+        assert!(Object::new("fun/test.jpg").info().is_none());
+    }
+
     #[tokio::test]
     async fn test_object_info() {
         let object = Object::new("app-config.json");
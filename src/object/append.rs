@@ -0,0 +1,208 @@
+use reqwest::{Method, Response, StatusCode};
+
+use crate::{
+    client::Client,
+    error::{OssError, ServiceErrorCode},
+    object::UploadOptions,
+    types::CanonicalizedResource,
+    Object,
+};
+
+const NEXT_APPEND_POSITION: &str = "x-oss-next-append-position";
+
+/// # 追加上传的写入器
+///
+/// 记录下一次追加的位置，每次追加成功后自动更新
+///
+/// ```no_run
+/// # use aliyun_oss_client::{Client, Object};
+/// # use aliyun_oss_client::error::OssError;
+/// # async fn run(client: &Client) -> Result<(), OssError> {
+/// let mut appender = Object::new("app.log").appender(0);
+/// appender.append("line 1\n".into(), client).await?;
+/// match appender.append("line 2\n".into(), client).await {
+///     // 位置不一致时，可以使用 OSS 返回的位置继续追加
+///     Err(OssError::PositionNotEqualToLength(position)) => appender.set_position(position),
+///     res => {
+///         res?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Appender {
+    object: Object,
+    position: u64,
+    options: UploadOptions,
+}
+
+impl Appender {
+    /// 下一次追加的位置
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// 文件的 `Content-Type`，只在创建文件的第一次追加时生效
+    pub fn content_type<T: Into<String>>(mut self, content_type: T) -> Self {
+        self.options = self.options.content_type(content_type);
+        self
+    }
+
+    /// 在当前位置追加内容，返回下一次追加的位置
+    pub async fn append(&mut self, content: Vec<u8>, client: &Client) -> Result<u64, OssError> {
+        self.position = self
+            .object
+            .append_with(content, self.position, &self.options, client)
+            .await?;
+        Ok(self.position)
+    }
+}
+
+impl Object {
+    /// 创建一个从 `position` 开始追加内容的写入器，新文件的起始位置为 0
+    pub fn appender(&self, position: u64) -> Appender {
+        Appender {
+            object: self.clone(),
+            position,
+            options: UploadOptions::new(),
+        }
+    }
+
+    /// # 追加上传（AppendObject）
+    ///
+    /// 在 `position` 处追加内容，返回 OSS 给出的下一次追加的位置。
+    ///
+    /// `position` 与文件当前长度不一致时，返回 [`OssError::PositionNotEqualToLength`]，
+    /// 其中携带文件的当前长度
+    pub async fn append(
+        &self,
+        content: Vec<u8>,
+        position: u64,
+        client: &Client,
+    ) -> Result<u64, OssError> {
+        self.append_with(content, position, &UploadOptions::new(), client)
+            .await
+    }
+
+    /// 与 [`append`](Object::append) 相同，可以指定 `Content-Type` 等参数，只在创建文件时生效
+    pub async fn append_with(
+        &self,
+        content: Vec<u8>,
+        position: u64,
+        options: &UploadOptions,
+        client: &Client,
+    ) -> Result<u64, OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        let query = format!("append&position={}", position);
+        let mut url = self.to_url(bucket);
+        url.set_query(Some(&query));
+        let method = Method::POST;
+        let resource =
+            CanonicalizedResource::new(format!("/{}/{}?{}", bucket.as_str(), self.path, query));
+
+        let response = client
+            .request(method, url, resource)
+            .headers(options.to_headers()?)
+            .body(content)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return next_append_position(&response)?.ok_or(OssError::NoFoundNextAppendPosition);
+        }
+
+        // 错误响应中没有可用的位置时，返回 OSS 的原始错误
+        let next_position = next_append_position(&response).ok().flatten();
+        let error = OssError::from_response(response).await;
+        match next_position {
+            Some(position)
+                if status == StatusCode::CONFLICT
+                    && error.service_code()
+                        == Some(&ServiceErrorCode::PositionNotEqualToLength) =>
+            {
+                Err(OssError::PositionNotEqualToLength(position))
+            }
+            _ => Err(error),
+        }
+    }
+}
+
+fn next_append_position(response: &Response) -> Result<Option<u64>, OssError> {
+    match response.headers().get(NEXT_APPEND_POSITION) {
+        Some(position) => Ok(Some(position.to_str()?.parse()?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::OssError,
+        test_server::{self, serve_raw},
+        Object,
+    };
+
+    fn conflict(position: Option<&str>) -> String {
+        let body = "<Error><Code>PositionNotEqualToLength</Code><Message>x</Message></Error>";
+        format!(
+            "HTTP/1.1 409 Conflict\r\n{}content-length: {}\r\nconnection: close\r\n\r\n{}",
+            position
+                .map(|p| format!("x-oss-next-append-position: {}\r\n", p))
+                .unwrap_or_default(),
+            body.len(),
+            body
+        )
+    }
+
+    #[tokio::test]
+    async fn test_position_not_equal_to_length() {
+        let (server, _handle) = serve_raw(vec![conflict(Some("12"))]);
+        let err = Object::new("app.log")
+            .append("abc".into(), 3, &test_server::client(&server))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OssError::PositionNotEqualToLength(12)));
+
+        // 没有或无法解析下一次追加的位置时，返回 OSS 的原始错误
+        for position in [None, Some("abc")] {
+            let (server, _handle) = serve_raw(vec![conflict(position)]);
+            let err = Object::new("app.log")
+                .append("abc".into(), 3, &test_server::client(&server))
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.service_code().unwrap().as_str(),
+                "PositionNotEqualToLength"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_appender() {
+        let response = |position: u64| {
+            format!(
+                "HTTP/1.1 200 OK\r\nx-oss-next-append-position: {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                position
+            )
+        };
+        let (server, handle) = serve_raw(vec![response(3), response(6)]);
+        let client = test_server::client(&server);
+
+        let mut appender = Object::new("app.log")
+            .appender(0)
+            .content_type("text/plain");
+        assert_eq!(appender.append("abc".into(), &client).await.unwrap(), 3);
+        assert_eq!(appender.append("def".into(), &client).await.unwrap(), 6);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "POST /app.log?append&position=0 HTTP/1.1");
+        assert_eq!(requests[1].line, "POST /app.log?append&position=3 HTTP/1.1");
+        assert_eq!(requests[0].header("content-type"), Some("text/plain"));
+    }
+}
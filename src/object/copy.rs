@@ -0,0 +1,410 @@
+use chrono::{DateTime, Utc};
+use reqwest::{header::HeaderMap, Method};
+
+use crate::{
+    bucket::Bucket,
+    client::Client,
+    error::{check_response, OssError},
+    object::{
+        download::{http_date, ByteRange},
+        multipart::{MultipartUpload, MAX_PART_NUMBER},
+    },
+    types::{encode_path, CanonicalizedResource},
+    Object,
+};
+
+/// 超过该大小的文件会使用分片复制（UploadPartCopy）
+pub const MULTIPART_COPY_THRESHOLD: u64 = 1024 * 1024 * 1024;
+
+/// 分片复制时每个分片的最小尺寸
+const MULTIPART_COPY_PART_SIZE: u64 = 100 * 1024 * 1024;
+
+/// 复制时如何处理目标文件的元信息，对应 `x-oss-metadata-directive`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataDirective {
+    /// 复制源文件的元信息，默认值
+    #[default]
+    Copy,
+    /// 使用请求中指定的元信息
+    Replace,
+}
+
+impl MetadataDirective {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetadataDirective::Copy => "COPY",
+            MetadataDirective::Replace => "REPLACE",
+        }
+    }
+}
+
+/// # 复制文件的参数
+///
+/// ```
+/// # use aliyun_oss_client::object::{CopyOptions, MetadataDirective};
+/// let options = CopyOptions::new()
+///     .metadata_directive(MetadataDirective::Replace)
+///     .meta("author", "tu6ge")
+///     .if_match("\"5B3C1A2E053D763E1B002CC607C5A0FE\"");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    metadata_directive: Option<MetadataDirective>,
+    meta: Vec<(String, String)>,
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+    if_modified_since: Option<DateTime<Utc>>,
+    if_unmodified_since: Option<DateTime<Utc>>,
+    multipart: bool,
+}
+
+impl CopyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn metadata_directive(mut self, directive: MetadataDirective) -> Self {
+        self.metadata_directive = Some(directive);
+        self
+    }
+
+    /// 自定义元信息，会以 `x-oss-meta-{key}` 的形式发送，仅在 `Replace` 时生效
+    pub fn meta<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.meta.push((key.into(), value.into()));
+        self
+    }
+
+    /// 源文件的 ETag 匹配时才复制
+    pub fn if_match<E: Into<String>>(mut self, etag: E) -> Self {
+        self.if_match = Some(etag.into());
+        self
+    }
+
+    /// 源文件的 ETag 不匹配时才复制
+    pub fn if_none_match<E: Into<String>>(mut self, etag: E) -> Self {
+        self.if_none_match = Some(etag.into());
+        self
+    }
+
+    /// 源文件在指定时间之后被修改过才复制
+    pub fn if_modified_since(mut self, time: DateTime<Utc>) -> Self {
+        self.if_modified_since = Some(time);
+        self
+    }
+
+    /// 源文件在指定时间之后没有被修改过才复制
+    pub fn if_unmodified_since(mut self, time: DateTime<Utc>) -> Self {
+        self.if_unmodified_since = Some(time);
+        self
+    }
+
+    /// 使用分片复制（UploadPartCopy），源文件的大小未知时会先查询一次元信息
+    pub fn multipart(mut self, multipart: bool) -> Self {
+        self.multipart = multipart;
+        self
+    }
+
+    /// 复制源文件时的条件头信息
+    fn condition_headers(&self) -> Result<HeaderMap, OssError> {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = &self.if_match {
+            headers.insert("x-oss-copy-source-if-match", etag.try_into()?);
+        }
+        if let Some(etag) = &self.if_none_match {
+            headers.insert("x-oss-copy-source-if-none-match", etag.try_into()?);
+        }
+        if let Some(time) = &self.if_modified_since {
+            headers.insert(
+                "x-oss-copy-source-if-modified-since",
+                http_date(time).try_into()?,
+            );
+        }
+        if let Some(time) = &self.if_unmodified_since {
+            headers.insert(
+                "x-oss-copy-source-if-unmodified-since",
+                http_date(time).try_into()?,
+            );
+        }
+        Ok(headers)
+    }
+
+    /// 目标文件的元信息头，只有 `Replace` 时才会发送
+    fn meta_headers(&self) -> Result<HeaderMap, OssError> {
+        let mut headers = HeaderMap::new();
+        if self.metadata_directive != Some(MetadataDirective::Replace) {
+            return Ok(headers);
+        }
+        for (key, value) in self.meta.iter() {
+            let name: reqwest::header::HeaderName = format!("x-oss-meta-{}", key)
+                .parse()
+                .map_err(|_| OssError::InvalidMetaKey)?;
+            headers.insert(name, value.try_into()?);
+        }
+        Ok(headers)
+    }
+}
+
+pub(super) fn copy_source(bucket: &Bucket, object: &Object) -> String {
+    format!("/{}/{}", bucket.as_str(), encode_path(&object.path))
+}
+
+impl Object {
+    /// 在当前 bucket 内复制文件（CopyObject），文件内容不会经过本地
+    pub async fn copy_to(
+        &self,
+        target: &Object,
+        options: &CopyOptions,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        self.copy_to_bucket(bucket, target, options, client).await
+    }
+
+    /// 将当前 bucket 中的文件复制到 `target_bucket`
+    ///
+    /// 源文件的大小已知（例如由列表接口返回）且超过 1GB，或者设置了
+    /// [`multipart`](CopyOptions::multipart) 时使用分片复制，此时 `Copy` 模式不会保留源文件的元信息
+    pub async fn copy_to_bucket(
+        &self,
+        target_bucket: &Bucket,
+        target: &Object,
+        options: &CopyOptions,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let size = match self.size {
+            Some(size) => Some(size),
+            None if options.multipart => Some(self.get_info(client).await?.size()),
+            None => None,
+        };
+        if let Some(size) = size {
+            if options.multipart || size > MULTIPART_COPY_THRESHOLD {
+                return self
+                    .multipart_copy(target_bucket, target, size, options, client)
+                    .await;
+            }
+        }
+
+        let source_bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        let url = target.to_url(target_bucket);
+        let method = Method::PUT;
+        let resource =
+            CanonicalizedResource::new(format!("/{}/{}", target_bucket.as_str(), target.path));
+
+        let mut headers = options.condition_headers()?;
+        headers.insert(
+            "x-oss-copy-source",
+            copy_source(source_bucket, self).try_into()?,
+        );
+        if let Some(directive) = options.metadata_directive {
+            headers.insert("x-oss-metadata-directive", directive.as_str().try_into()?);
+        }
+        headers.extend(options.meta_headers()?);
+
+        let response = client
+            .request(method, url, resource)
+            .headers(headers)
+            .send()
+            .await?;
+
+        check_response(response).await?;
+        Ok(())
+    }
+
+    /// 在当前 bucket 内重命名文件：先在服务端复制到 `target`，成功后再删除当前文件
+    ///
+    /// `target` 与当前文件相同时返回 [`OssError::InvalidRenameTarget`]，以免复制后删除了文件本身
+    pub async fn rename_to(&self, target: &Object, client: &Client) -> Result<(), OssError> {
+        if self == target {
+            return Err(OssError::InvalidRenameTarget);
+        }
+        self.copy_to(target, &CopyOptions::new(), client).await?;
+        self.delete(client).await
+    }
+
+    /// 将文件移动到 `target_bucket` 中的 `target`，成功后删除当前 bucket 中的文件
+    ///
+    /// 目标就是当前文件时返回 [`OssError::InvalidRenameTarget`]
+    pub async fn move_to_bucket(
+        &self,
+        target_bucket: &Bucket,
+        target: &Object,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        if bucket == target_bucket && self == target {
+            return Err(OssError::InvalidRenameTarget);
+        }
+        self.copy_to_bucket(target_bucket, target, &CopyOptions::new(), client)
+            .await?;
+        self.delete(client).await
+    }
+
+    async fn multipart_copy(
+        &self,
+        target_bucket: &Bucket,
+        target: &Object,
+        size: u64,
+        options: &CopyOptions,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let source_bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+
+        let mut upload =
+            MultipartUpload::initiate(target_bucket, &target.path, options.meta_headers()?, client)
+                .await?;
+
+        let part_size = MULTIPART_COPY_PART_SIZE.max(size.div_ceil(MAX_PART_NUMBER as u64));
+        let part_count = size.div_ceil(part_size);
+
+        for index in 0..part_count {
+            let start = index * part_size;
+            let end = (start + part_size).min(size) - 1;
+
+            let mut headers = options.condition_headers()?;
+            headers.insert(
+                "x-oss-copy-source-range",
+                ByteRange::Bounded(start, end).to_header().try_into()?,
+            );
+
+            let res = upload
+                .upload_part_copy_with_headers(
+                    index as u32 + 1,
+                    source_bucket,
+                    self,
+                    headers,
+                    client,
+                )
+                .await;
+            if let Err(e) = res {
+                let _ = upload.abort(client).await;
+                return Err(e);
+            }
+        }
+
+        upload.complete(client).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_source, CopyOptions, MetadataDirective};
+    use crate::{
+        bucket::Bucket,
+        error::OssError,
+        test_server::{self, serve, serve_raw},
+        types::EndPoint,
+        Object,
+    };
+
+    #[test]
+    fn test_copy_headers() {
+        let options = CopyOptions::new()
+            .metadata_directive(MetadataDirective::Replace)
+            .meta("author", "tu6ge")
+            .if_none_match("\"etag\"");
+
+        let headers = options.condition_headers().unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(
+            headers.get("x-oss-copy-source-if-none-match").unwrap(),
+            "\"etag\""
+        );
+
+        let headers = options.meta_headers().unwrap();
+        assert_eq!(headers.get("x-oss-meta-author").unwrap(), "tu6ge");
+
+        let source = copy_source(
+            &Bucket::new("foo", EndPoint::CN_QINGDAO),
+            &Object::new("dir/a b.txt"),
+        );
+        assert_eq!(source, "/foo/dir/a%20b.txt");
+    }
+
+    #[tokio::test]
+    async fn test_copy_to() {
+        // 大小未知时直接使用 CopyObject，不查询源文件的元信息
+        let (server, handle) = serve(vec![(200, String::new()), (200, String::new())]);
+        let client = test_server::client(&server);
+
+        let options = CopyOptions::new().meta("author", "tu6ge");
+        Object::new("a.txt")
+            .copy_to(&Object::new("b.txt"), &options, &client)
+            .await
+            .unwrap();
+        let options = options.metadata_directive(MetadataDirective::Replace);
+        Object::new("a.txt")
+            .copy_to(&Object::new("b.txt"), &options, &client)
+            .await
+            .unwrap();
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].line, "PUT /b.txt HTTP/1.1");
+        assert_eq!(
+            requests[0].header("x-oss-copy-source"),
+            Some("/bucket/a.txt")
+        );
+        assert!(requests[0].header("x-oss-meta-author").is_none());
+        assert_eq!(
+            requests[1].header("x-oss-metadata-directive"),
+            Some("REPLACE")
+        );
+        assert_eq!(requests[1].header("x-oss-meta-author"), Some("tu6ge"));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_copy() {
+        let ok = |body: &str| {
+            format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        };
+        let (server, handle) = serve_raw(vec![
+            ok("<InitiateMultipartUploadResult><UploadId>upload-1</UploadId></InitiateMultipartUploadResult>"),
+            ok("<CopyPartResult><ETag>\"e1\"</ETag></CopyPartResult>"),
+            ok(""),
+        ]);
+
+        // 列表接口返回的文件已知大小，不需要再查询元信息
+        let source = Object::from_list_xml("<Key>a.txt</Key><Size>10</Size>").unwrap();
+        source
+            .copy_to(
+                &Object::new("b.txt"),
+                &CopyOptions::new().multipart(true),
+                &test_server::client(&server),
+            )
+            .await
+            .unwrap();
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "POST /b.txt?uploads HTTP/1.1");
+        assert_eq!(
+            requests[1].line,
+            "PUT /b.txt?partNumber=1&uploadId=upload-1 HTTP/1.1"
+        );
+        assert_eq!(
+            requests[1].header("x-oss-copy-source-range"),
+            Some("bytes=0-9")
+        );
+        assert_eq!(requests[2].line, "POST /b.txt?uploadId=upload-1 HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_rename_to_self() {
+        // 不会发出任何请求
+        let client = test_server::client("http://127.0.0.1:1");
+        let object = Object::new("a.txt");
+
+        let err = object.rename_to(&object, &client).await.unwrap_err();
+        assert!(matches!(err, OssError::InvalidRenameTarget));
+
+        let bucket = client.bucket().unwrap();
+        let err = object
+            .move_to_bucket(bucket, &Object::new("a.txt"), &client)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OssError::InvalidRenameTarget));
+    }
+}
@@ -0,0 +1,294 @@
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{
+        HeaderMap, CONTENT_RANGE, ETAG, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        IF_UNMODIFIED_SINCE, RANGE,
+    },
+    Method, StatusCode,
+};
+
+use crate::{client::Client, error::OssError, types::CanonicalizedResource, Object};
+
+/// # 下载的字节范围
+///
+/// 对应 HTTP 的 `Range` 请求头，字节位置从 0 开始，`Bounded` 包含结束位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// `bytes=start-end`
+    Bounded(u64, u64),
+    /// `bytes=start-`，从 start 读取到结尾
+    From(u64),
+    /// `bytes=-len`，读取最后 len 个字节
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// ```
+    /// # use aliyun_oss_client::object::ByteRange;
+    /// assert_eq!(ByteRange::Bounded(0, 99).to_header(), "bytes=0-99");
+    /// assert_eq!(ByteRange::From(100).to_header(), "bytes=100-");
+    /// assert_eq!(ByteRange::Suffix(8).to_header(), "bytes=-8");
+    /// ```
+    pub fn to_header(&self) -> String {
+        match self {
+            ByteRange::Bounded(start, end) => format!("bytes={}-{}", start, end),
+            ByteRange::From(start) => format!("bytes={}-", start),
+            ByteRange::Suffix(len) => format!("bytes=-{}", len),
+        }
+    }
+}
+
+/// 206 响应中 `Content-Range` 描述的实际返回范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    start: u64,
+    end: u64,
+    total: Option<u64>,
+}
+
+impl ContentRange {
+    /// 解析 `bytes 0-9/443` 格式的字符串，总长度未知时为 `bytes 0-9/*`
+    /// ```
+    /// # use aliyun_oss_client::object::ContentRange;
+    /// let range = ContentRange::parse("bytes 0-9/443").unwrap();
+    /// assert_eq!(range.start(), 0);
+    /// assert_eq!(range.end(), 9);
+    /// assert_eq!(range.total(), Some(443));
+    ///
+    /// assert_eq!(ContentRange::parse("bytes 0-9/*").unwrap().total(), None);
+    /// assert!(ContentRange::parse("0-9").is_none());
+    /// ```
+    pub fn parse(s: &str) -> Option<ContentRange> {
+        let s = s.strip_prefix("bytes ")?;
+        let (range, total) = s.split_once('/')?;
+        let (start, end) = range.split_once('-')?;
+        let total = match total {
+            "*" => None,
+            t => Some(t.parse().ok()?),
+        };
+
+        Some(ContentRange {
+            start: start.parse().ok()?,
+            end: end.parse().ok()?,
+            total,
+        })
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// 文件的总长度
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+}
+
+/// # 范围下载和条件下载的参数
+///
+/// ```
+/// # use aliyun_oss_client::object::{ByteRange, DownloadOptions};
+/// let options = DownloadOptions::new()
+///     .range(ByteRange::Suffix(8))
+///     .if_none_match("\"5B3C1A2E053D763E1B002CC607C5A0FE\"");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    range: Option<ByteRange>,
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+    if_modified_since: Option<DateTime<Utc>>,
+    if_unmodified_since: Option<DateTime<Utc>>,
+}
+
+impl DownloadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn range(mut self, range: ByteRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// ETag 匹配时才返回内容，否则返回 412
+    pub fn if_match<E: Into<String>>(mut self, etag: E) -> Self {
+        self.if_match = Some(etag.into());
+        self
+    }
+
+    /// ETag 不匹配时才返回内容，否则返回 304
+    pub fn if_none_match<E: Into<String>>(mut self, etag: E) -> Self {
+        self.if_none_match = Some(etag.into());
+        self
+    }
+
+    /// 文件在指定时间之后被修改过才返回内容，否则返回 304
+    pub fn if_modified_since(mut self, time: DateTime<Utc>) -> Self {
+        self.if_modified_since = Some(time);
+        self
+    }
+
+    /// 文件在指定时间之后没有被修改过才返回内容，否则返回 412
+    pub fn if_unmodified_since(mut self, time: DateTime<Utc>) -> Self {
+        self.if_unmodified_since = Some(time);
+        self
+    }
+
+    pub(crate) fn to_headers(&self) -> Result<HeaderMap, OssError> {
+        let mut headers = HeaderMap::new();
+        if let Some(range) = &self.range {
+            headers.insert(RANGE, range.to_header().try_into()?);
+        }
+        if let Some(etag) = &self.if_match {
+            headers.insert(IF_MATCH, etag.try_into()?);
+        }
+        if let Some(etag) = &self.if_none_match {
+            headers.insert(IF_NONE_MATCH, etag.try_into()?);
+        }
+        if let Some(time) = &self.if_modified_since {
+            headers.insert(IF_MODIFIED_SINCE, http_date(time).try_into()?);
+        }
+        if let Some(time) = &self.if_unmodified_since {
+            headers.insert(IF_UNMODIFIED_SINCE, http_date(time).try_into()?);
+        }
+        Ok(headers)
+    }
+}
+
+pub(super) fn http_date(time: &DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %T GMT").to_string()
+}
+
+/// # 范围下载和条件下载的结果
+#[derive(Debug)]
+pub enum DownloadResponse {
+    /// 200，返回完整的文件内容
+    Full {
+        content: Vec<u8>,
+        etag: Option<String>,
+    },
+    /// 206，返回部分内容
+    Partial {
+        content: Vec<u8>,
+        range: ContentRange,
+        etag: Option<String>,
+    },
+    /// 304，文件未被修改
+    NotModified,
+    /// 412，不满足 `If-Match` 或 `If-Unmodified-Since` 条件
+    PreconditionFailed,
+}
+
+impl Object {
+    /// 带范围和条件的下载，不同的响应状态会返回不同的 [`DownloadResponse`]
+    pub async fn download_with(
+        &self,
+        options: &DownloadOptions,
+        client: &Client,
+    ) -> Result<DownloadResponse, OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        let url = self.to_url(bucket);
+        let method = Method::GET;
+        let resource = CanonicalizedResource::new(format!("/{}/{}", bucket.as_str(), self.path));
+
+        let response = client
+            .request(method, url, resource)
+            .headers(options.to_headers()?)
+            .send()
+            .await?;
+
+        let etag = match response.headers().get(ETAG) {
+            Some(etag) => Some(etag.to_str()?.to_owned()),
+            None => None,
+        };
+
+        match response.status() {
+            StatusCode::OK => Ok(DownloadResponse::Full {
+                content: response.bytes().await?.into(),
+                etag,
+            }),
+            StatusCode::PARTIAL_CONTENT => {
+                let range = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|r| r.to_str().ok())
+                    .and_then(ContentRange::parse)
+                    .ok_or(OssError::NoFoundContentRange)?;
+                Ok(DownloadResponse::Partial {
+                    content: response.bytes().await?.into(),
+                    range,
+                    etag,
+                })
+            }
+            StatusCode::NOT_MODIFIED => Ok(DownloadResponse::NotModified),
+            StatusCode::PRECONDITION_FAILED => Ok(DownloadResponse::PreconditionFailed),
+            _ => Err(OssError::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{ByteRange, DownloadOptions, DownloadResponse};
+    use crate::{
+        test_server::{self, serve_raw},
+        Object,
+    };
+
+    #[test]
+    fn test_options_headers() {
+        let time = Utc.with_ymd_and_hms(2022, 10, 6, 20, 40, 0).unwrap();
+        let headers = DownloadOptions::new()
+            .range(ByteRange::Bounded(10, 19))
+            .if_match("\"etag\"")
+            .if_modified_since(time)
+            .to_headers()
+            .unwrap();
+
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers.get("range").unwrap(), "bytes=10-19");
+        assert_eq!(headers.get("if-match").unwrap(), "\"etag\"");
+        assert_eq!(
+            headers.get("if-modified-since").unwrap(),
+            "Thu, 06 Oct 2022 20:40:00 GMT"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_range() {
+        let (server, handle) = serve_raw(vec![
+            "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes 6-10/11\r\netag: \"abc\"\r\ncontent-length: 5\r\nconnection: close\r\n\r\nworld".to_owned(),
+        ]);
+
+        let options = DownloadOptions::new().range(ByteRange::From(6));
+        let res = Object::new("abc.txt")
+            .download_with(&options, &test_server::client(&server))
+            .await
+            .unwrap();
+        match res {
+            DownloadResponse::Partial {
+                content,
+                range,
+                etag,
+            } => {
+                assert_eq!(content, b"world");
+                assert_eq!(
+                    (range.start(), range.end(), range.total()),
+                    (6, 10, Some(11))
+                );
+                assert_eq!(etag.as_deref(), Some("\"abc\""));
+            }
+            res => panic!("unexpected response: {:?}", res),
+        }
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].header("range"), Some("bytes=6-"));
+    }
+}
@@ -0,0 +1,632 @@
+use std::path::Path;
+
+use reqwest::{header::HeaderMap, Method};
+use serde::{Deserialize, Serialize};
+use serde_xml_rs::from_str;
+use tokio::io::AsyncReadExt;
+
+use crate::{
+    bucket::{xml_unescape, Bucket},
+    client::Client,
+    error::{check_response, OssError},
+    object::{copy::copy_source, download::ByteRange, UploadOptions},
+    types::CanonicalizedResource,
+    Object,
+};
+
+/// 分片的最小尺寸（最后一个分片除外）
+pub const MIN_PART_SIZE: u64 = 100 * 1024;
+
+/// 分片编号的最大值
+pub const MAX_PART_NUMBER: u32 = 10000;
+
+/// # 分片上传会话
+///
+/// 通过 [`Object::initiate_multipart_upload`] 创建，会记录每个已上传分片的编号和 ETag，
+/// 调用 [`complete`](MultipartUpload::complete) 时再统一提交给 OSS
+#[derive(Debug, Clone)]
+pub struct MultipartUpload {
+    bucket: Bucket,
+    path: String,
+    upload_id: String,
+    parts: Vec<UploadedPart>,
+}
+
+/// 已上传成功的分片
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadedPart {
+    #[serde(rename = "PartNumber")]
+    part_number: u32,
+    #[serde(rename = "ETag")]
+    etag: String,
+}
+
+impl UploadedPart {
+    pub fn new<E: Into<String>>(part_number: u32, etag: E) -> Self {
+        UploadedPart {
+            part_number,
+            etag: etag.into(),
+        }
+    }
+
+    pub fn part_number(&self) -> u32 {
+        self.part_number
+    }
+
+    pub fn etag(&self) -> &str {
+        &self.etag
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListPartsResult {
+    #[serde(rename = "IsTruncated")]
+    is_truncated: bool,
+    #[serde(rename = "NextPartNumberMarker")]
+    next_marker: Option<u32>,
+    #[serde(rename = "Part", default)]
+    parts: Vec<UploadedPart>,
+}
+
+impl Object {
+    /// 初始化一个分片上传事件（InitiateMultipartUpload）
+    ///
+    /// 会话会记住当前 client 中的 bucket，后续分片都会上传到该 bucket
+    pub async fn initiate_multipart_upload(
+        &self,
+        client: &Client,
+    ) -> Result<MultipartUpload, OssError> {
+        self.initiate_multipart_upload_with(&UploadOptions::new(), client)
+            .await
+    }
+
+    /// 与 [`initiate_multipart_upload`](Object::initiate_multipart_upload) 相同，
+    /// 合并后文件的 `Content-Type` 等参数在这里指定，上传分片时不再单独设置
+    pub async fn initiate_multipart_upload_with(
+        &self,
+        options: &UploadOptions,
+        client: &Client,
+    ) -> Result<MultipartUpload, OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        MultipartUpload::initiate(bucket, &self.path, options.to_headers()?, client).await
+    }
+
+    /// 将本地文件按 `part_size` 切片，逐片上传
+    ///
+    /// 同一时刻内存中只保留一个分片的内容，任意分片失败时会取消本次上传事件。
+    /// 切片数量超过 [`MAX_PART_NUMBER`] 时，在初始化上传事件之前返回 [`OssError::InvalidPartSize`]
+    pub async fn upload_file_multipart<P: AsRef<Path>>(
+        &self,
+        file: P,
+        part_size: u64,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        if part_size < MIN_PART_SIZE {
+            return Err(OssError::InvalidPartSize);
+        }
+
+        let mut file = tokio::fs::File::open(file).await?;
+        let part_count = file.metadata().await?.len().div_ceil(part_size).max(1);
+        if part_count > MAX_PART_NUMBER as u64 {
+            return Err(OssError::InvalidPartSize);
+        }
+        let mut upload = self.initiate_multipart_upload(client).await?;
+
+        loop {
+            let mut content = Vec::new();
+            if let Err(e) = (&mut file).take(part_size).read_to_end(&mut content).await {
+                // 取消失败时仍然返回原始错误，未清理的分片可以通过生命周期规则回收
+                let _ = upload.abort(client).await;
+                return Err(e.into());
+            }
+            if content.is_empty() && !upload.parts.is_empty() {
+                break;
+            }
+            let is_last = (content.len() as u64) < part_size;
+
+            if let Err(e) = upload.upload_part(content, client).await {
+                let _ = upload.abort(client).await;
+                return Err(e);
+            }
+            if is_last {
+                break;
+            }
+        }
+
+        upload.complete(client).await
+    }
+}
+
+impl MultipartUpload {
+    /// 在指定的 bucket 中初始化上传事件，`headers` 中可以携带 `x-oss-meta-*` 等元信息
+    pub(crate) async fn initiate(
+        bucket: &Bucket,
+        path: &str,
+        headers: HeaderMap,
+        client: &Client,
+    ) -> Result<MultipartUpload, OssError> {
+        let mut url = bucket.to_url();
+        url.set_path(path);
+        url.set_query(Some("uploads"));
+        let method = Method::POST;
+        let resource = CanonicalizedResource::new(format!("/{}/{}?uploads", bucket.as_str(), path));
+
+        let response = client
+            .request(method, url, resource)
+            .headers(headers)
+            .send()
+            .await?;
+
+        let response = check_response(response).await?;
+        let content = response.text().await?;
+
+        let upload_id =
+            Bucket::parse_item(&content, "UploadId").ok_or(OssError::NoFoundUploadId)?;
+
+        Ok(MultipartUpload::new(
+            bucket.clone(),
+            path.to_owned(),
+            upload_id.to_owned(),
+        ))
+    }
+
+    pub(crate) fn new(bucket: Bucket, path: String, upload_id: String) -> Self {
+        MultipartUpload {
+            bucket,
+            path,
+            upload_id,
+            parts: Vec::new(),
+        }
+    }
+
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+
+    pub fn bucket(&self) -> &Bucket {
+        &self.bucket
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 已上传成功的分片，按分片编号排序
+    pub fn parts(&self) -> &[UploadedPart] {
+        &self.parts
+    }
+
+    /// 下一个未使用的分片编号
+    /// ```
+    /// # use aliyun_oss_client::{Bucket, EndPoint};
+    /// # use aliyun_oss_client::object::{MultipartUpload, UploadedPart};
+    /// let bucket = Bucket::new("foo", EndPoint::CN_QINGDAO);
+    /// let upload = MultipartUpload::resume(bucket, "a.zip", "id", vec![]);
+    /// assert_eq!(upload.next_part_number(), 1);
+    ///
+    /// let bucket = Bucket::new("foo", EndPoint::CN_QINGDAO);
+    /// let upload = MultipartUpload::resume(bucket, "a.zip", "id", vec![UploadedPart::new(3, "etag")]);
+    /// assert_eq!(upload.next_part_number(), 4);
+    /// ```
+    pub fn next_part_number(&self) -> u32 {
+        self.parts.last().map(|p| p.part_number + 1).unwrap_or(1)
+    }
+
+    /// 根据已知的 upload id 和已上传的分片恢复会话
+    pub fn resume<P: Into<String>, U: Into<String>>(
+        bucket: Bucket,
+        path: P,
+        upload_id: U,
+        mut parts: Vec<UploadedPart>,
+    ) -> Self {
+        parts.sort_by_key(|p| p.part_number);
+        parts.dedup_by_key(|p| p.part_number);
+        MultipartUpload {
+            bucket,
+            path: path.into(),
+            upload_id: upload_id.into(),
+            parts,
+        }
+    }
+
+    fn to_url(&self, query: &str) -> url::Url {
+        let mut url = self.bucket.to_url();
+        url.set_path(&self.path);
+        url.set_query(Some(query));
+        url
+    }
+
+    fn resource(&self, query: &str) -> CanonicalizedResource {
+        CanonicalizedResource::new(format!("/{}/{}?{}", self.bucket.as_str(), self.path, query))
+    }
+
+    /// 使用下一个分片编号上传分片（UploadPart）
+    ///
+    /// 分片请求不带 `Content-Type`，文件的类型通过
+    /// [`initiate_multipart_upload_with`](Object::initiate_multipart_upload_with) 指定
+    pub async fn upload_part(
+        &mut self,
+        content: Vec<u8>,
+        client: &Client,
+    ) -> Result<&UploadedPart, OssError> {
+        let part_number = self.next_part_number();
+        self.upload_part_with_number(part_number, content, client)
+            .await
+    }
+
+    /// 使用指定的分片编号上传分片，编号范围是 1 到 10000
+    ///
+    /// 相同编号的分片重复上传时，会覆盖之前记录的 ETag
+    pub async fn upload_part_with_number(
+        &mut self,
+        part_number: u32,
+        content: Vec<u8>,
+        client: &Client,
+    ) -> Result<&UploadedPart, OssError> {
+        if part_number == 0 || part_number > MAX_PART_NUMBER {
+            return Err(OssError::InvalidPartNumber);
+        }
+
+        let query = format!("partNumber={}&uploadId={}", part_number, self.upload_id);
+        let url = self.to_url(&query);
+        let method = Method::PUT;
+        let resource = self.resource(&query);
+
+        let response = client
+            .request(method, url, resource)
+            .body(content)
+            .send()
+            .await?;
+
+        let response = check_response(response).await?;
+
+        let etag = response
+            .headers()
+            .get("etag")
+            .ok_or(OssError::NoFoundEtag)?
+            .to_str()?
+            .to_owned();
+
+        Ok(self.insert_part(UploadedPart::new(part_number, etag)))
+    }
+
+    /// 从已有的文件中复制一段内容作为下一个分片（UploadPartCopy）
+    ///
+    /// `range` 为 `None` 时复制整个源文件
+    pub async fn upload_part_copy(
+        &mut self,
+        source_bucket: &Bucket,
+        source: &Object,
+        range: Option<ByteRange>,
+        client: &Client,
+    ) -> Result<&UploadedPart, OssError> {
+        let part_number = self.next_part_number();
+        let mut headers = HeaderMap::new();
+        if let Some(range) = range {
+            headers.insert("x-oss-copy-source-range", range.to_header().try_into()?);
+        }
+        self.upload_part_copy_with_headers(part_number, source_bucket, source, headers, client)
+            .await
+    }
+
+    pub(crate) async fn upload_part_copy_with_headers(
+        &mut self,
+        part_number: u32,
+        source_bucket: &Bucket,
+        source: &Object,
+        mut headers: HeaderMap,
+        client: &Client,
+    ) -> Result<&UploadedPart, OssError> {
+        if part_number == 0 || part_number > MAX_PART_NUMBER {
+            return Err(OssError::InvalidPartNumber);
+        }
+
+        let query = format!("partNumber={}&uploadId={}", part_number, self.upload_id);
+        let url = self.to_url(&query);
+        let method = Method::PUT;
+        let resource = self.resource(&query);
+
+        headers.insert(
+            "x-oss-copy-source",
+            copy_source(source_bucket, source).try_into()?,
+        );
+        let response = client
+            .request(method, url, resource)
+            .headers(headers)
+            .send()
+            .await?;
+
+        let response = check_response(response).await?;
+        let content = response.text().await?;
+
+        // 响应体中的 ETag 经过了 XML 转义，还原后与 UploadPart 响应头中的 ETag 保持一致
+        let etag = Bucket::parse_item(&content, "ETag")
+            .map(xml_unescape)
+            .ok_or(OssError::NoFoundEtag)?;
+
+        Ok(self.insert_part(UploadedPart::new(part_number, etag)))
+    }
+
+    /// 查询 OSS 已确认接收的分片（ListParts），会自动翻页
+    pub async fn list_parts(&self, client: &Client) -> Result<Vec<UploadedPart>, OssError> {
+        let mut list = Vec::new();
+        let mut marker = None;
+        loop {
+            let query = format!("uploadId={}", self.upload_id);
+            let url = match marker {
+                Some(m) => self.to_url(&format!("{}&part-number-marker={}", query, m)),
+                None => self.to_url(&query),
+            };
+            let method = Method::GET;
+            let resource = self.resource(&query);
+
+            let response = client.request(method, url, resource).send().await?;
+
+            let response = check_response(response).await?;
+            let content = response.text().await?;
+
+            let res: ListPartsResult = from_str(&content)?;
+            list.extend(res.parts);
+
+            match res.next_marker {
+                Some(m) if res.is_truncated => marker = Some(m),
+                _ => break,
+            }
+        }
+
+        Ok(list)
+    }
+
+    fn insert_part(&mut self, part: UploadedPart) -> &UploadedPart {
+        let index = match self
+            .parts
+            .binary_search_by_key(&part.part_number, |p| p.part_number)
+        {
+            Ok(index) => {
+                self.parts[index] = part;
+                index
+            }
+            Err(index) => {
+                self.parts.insert(index, part);
+                index
+            }
+        };
+        &self.parts[index]
+    }
+
+    fn complete_body(&self) -> String {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for part in self.parts.iter() {
+            body += "<Part><PartNumber>";
+            body += &part.part_number.to_string();
+            body += "</PartNumber><ETag>";
+            body += &part.etag;
+            body += "</ETag></Part>";
+        }
+        body += "</CompleteMultipartUpload>";
+        body
+    }
+
+    /// 提交所有已上传的分片，完成本次上传（CompleteMultipartUpload）
+    pub async fn complete(self, client: &Client) -> Result<(), OssError> {
+        let query = format!("uploadId={}", self.upload_id);
+        let url = self.to_url(&query);
+        let method = Method::POST;
+        let resource = self.resource(&query);
+
+        let response = client
+            .request(method, url, resource)
+            .body(self.complete_body())
+            .send()
+            .await?;
+
+        check_response(response).await?;
+        Ok(())
+    }
+
+    /// 取消本次上传，已上传的分片会被 OSS 删除（AbortMultipartUpload）
+    pub async fn abort(self, client: &Client) -> Result<(), OssError> {
+        let query = format!("uploadId={}", self.upload_id);
+        let url = self.to_url(&query);
+        let method = Method::DELETE;
+        let resource = self.resource(&query);
+
+        let response = client.request(method, url, resource).send().await?;
+
+        check_response(response).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ListPartsResult, MultipartUpload, UploadedPart, MAX_PART_NUMBER, MIN_PART_SIZE};
+    use crate::{
+        bucket::Bucket,
+        error::OssError,
+        test_server::{self, serve_raw},
+        types::EndPoint,
+        Object,
+    };
+
+    fn response(status: u16, etag: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {} X\r\netag: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            etag,
+            body.len(),
+            body
+        )
+    }
+
+    const INITIATE: &str =
+        "<InitiateMultipartUploadResult><UploadId>upload-1</UploadId></InitiateMultipartUploadResult>";
+
+    #[tokio::test]
+    async fn test_upload_file_multipart() {
+        let path = std::env::temp_dir().join("aliyun-oss-upload-multipart-test");
+        let content: Vec<u8> = (0..MIN_PART_SIZE * 2 + 10).map(|i| i as u8).collect();
+        tokio::fs::write(&path, &content).await.unwrap();
+
+        let (server, handle) = serve_raw(vec![
+            response(200, "", INITIATE),
+            response(200, "\"e1\"", ""),
+            response(200, "\"e2\"", ""),
+            response(200, "\"e3\"", ""),
+            response(200, "", ""),
+        ]);
+        Object::new("big.zip")
+            .upload_file_multipart(&path, MIN_PART_SIZE, &test_server::client(&server))
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "POST /big.zip?uploads HTTP/1.1");
+        for (i, request) in requests[1..4].iter().enumerate() {
+            assert_eq!(
+                request.line,
+                format!(
+                    "PUT /big.zip?partNumber={}&uploadId=upload-1 HTTP/1.1",
+                    i + 1
+                )
+            );
+        }
+        let size = MIN_PART_SIZE as usize;
+        assert_eq!(requests[1].body, &content[..size]);
+        assert_eq!(requests[2].body, &content[size..size * 2]);
+        assert_eq!(requests[3].body, &content[size * 2..]);
+
+        assert_eq!(requests[4].line, "POST /big.zip?uploadId=upload-1 HTTP/1.1");
+        let body = String::from_utf8_lossy(&requests[4].body);
+        assert!(body.contains("<PartNumber>3</PartNumber><ETag>\"e3\"</ETag>"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_file_multipart_abort() {
+        let path = std::env::temp_dir().join("aliyun-oss-upload-multipart-abort-test");
+        tokio::fs::write(&path, vec![0; MIN_PART_SIZE as usize + 1])
+            .await
+            .unwrap();
+
+        // 第二个分片失败，取消上传也失败时，返回分片的错误
+        let (server, handle) = serve_raw(vec![
+            response(200, "", INITIATE),
+            response(200, "\"e1\"", ""),
+            response(400, "", "<Error><Code>InvalidArgument</Code></Error>"),
+            response(403, "", "<Error><Code>AccessDenied</Code></Error>"),
+        ]);
+        let err = Object::new("big.zip")
+            .upload_file_multipart(&path, MIN_PART_SIZE, &test_server::client(&server))
+            .await
+            .unwrap_err();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(err.service_code().unwrap().as_str(), "InvalidArgument");
+        let requests = handle.join().unwrap();
+        assert_eq!(
+            requests[3].line,
+            "DELETE /big.zip?uploadId=upload-1 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_file_multipart_too_many_parts() {
+        let path = std::env::temp_dir().join("aliyun-oss-upload-multipart-parts-test");
+        // 稀疏文件，不会真正占用磁盘
+        let file = tokio::fs::File::create(&path).await.unwrap();
+        file.set_len(MIN_PART_SIZE * MAX_PART_NUMBER as u64 + 1)
+            .await
+            .unwrap();
+
+        // 在初始化上传事件之前返回，不会发出任何请求
+        let err = Object::new("big.zip")
+            .upload_file_multipart(
+                &path,
+                MIN_PART_SIZE,
+                &test_server::client("http://127.0.0.1:1"),
+            )
+            .await
+            .unwrap_err();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert!(matches!(err, OssError::InvalidPartSize));
+    }
+
+    #[tokio::test]
+    async fn test_upload_part_copy_etag() {
+        let (server, handle) = serve_raw(vec![response(
+            200,
+            "",
+            "<CopyPartResult><ETag>&quot;e1&quot;</ETag></CopyPartResult>",
+        )]);
+        let client = test_server::client(&server);
+        let bucket = client.bucket().unwrap().clone();
+
+        let mut upload =
+            MultipartUpload::new(bucket.clone(), "b.txt".to_owned(), "upload-1".to_owned());
+        let part = upload
+            .upload_part_copy(&bucket, &Object::new("a.txt"), None, &client)
+            .await
+            .unwrap();
+        assert_eq!(part, &UploadedPart::new(1, "\"e1\""));
+        assert_eq!(
+            upload.complete_body(),
+            "<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>\"e1\"</ETag></Part></CompleteMultipartUpload>"
+        );
+
+        let requests = handle.join().unwrap();
+        assert_eq!(
+            requests[0].line,
+            "PUT /b.txt?partNumber=1&uploadId=upload-1 HTTP/1.1"
+        );
+    }
+
+    #[test]
+    fn test_parse_list_parts() {
+        let xml = r#"<ListPartsResult>
+            <Bucket>foo</Bucket>
+            <Key>big.zip</Key>
+            <UploadId>0004B999EF5A239BB9138C6227D6</UploadId>
+            <NextPartNumberMarker>5</NextPartNumberMarker>
+            <MaxParts>1000</MaxParts>
+            <IsTruncated>false</IsTruncated>
+            <Part>
+                <PartNumber>1</PartNumber>
+                <LastModified>2012-02-23T07:01:34.000Z</LastModified>
+                <ETag>"3349DC700140D7F86A0784842780****"</ETag>
+                <Size>6291456</Size>
+            </Part>
+        </ListPartsResult>"#;
+
+        let res: ListPartsResult = serde_xml_rs::from_str(xml).unwrap();
+        assert!(!res.is_truncated);
+        assert_eq!(res.next_marker, Some(5));
+        assert_eq!(
+            res.parts,
+            vec![UploadedPart::new(1, "\"3349DC700140D7F86A0784842780****\"")]
+        );
+    }
+
+    #[test]
+    fn test_complete_body() {
+        let mut upload = MultipartUpload::new(
+            Bucket::new("foo", EndPoint::CN_QINGDAO),
+            "big.zip".to_owned(),
+            "0004B9894A22E5B1888A1E29F823".to_owned(),
+        );
+        upload.insert_part(UploadedPart::new(2, "\"B\""));
+        upload.insert_part(UploadedPart::new(1, "\"A\""));
+        upload.insert_part(UploadedPart::new(2, "\"C\""));
+
+        assert_eq!(upload.parts().len(), 2);
+        assert_eq!(upload.next_part_number(), 3);
+        assert_eq!(
+            upload.complete_body(),
+            "<CompleteMultipartUpload>\
+            <Part><PartNumber>1</PartNumber><ETag>\"A\"</ETag></Part>\
+            <Part><PartNumber>2</PartNumber><ETag>\"C\"</ETag></Part>\
+            </CompleteMultipartUpload>"
+        );
+    }
+}
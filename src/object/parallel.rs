@@ -0,0 +1,443 @@
+use std::{
+    io::SeekFrom,
+    path::{Path, PathBuf},
+};
+
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::{
+    client::Client,
+    error::OssError,
+    object::{
+        download::{ByteRange, DownloadOptions, DownloadResponse},
+        resumable::{read_checkpoint, write_checkpoint},
+    },
+    Object,
+};
+
+/// # 并发分片下载的参数
+///
+/// ```
+/// # use aliyun_oss_client::object::ParallelDownload;
+/// let options = ParallelDownload::new()
+///     .part_size(16 * 1024 * 1024)
+///     .concurrency(8)
+///     .checkpoint("big.zip.cp");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParallelDownload {
+    part_size: u64,
+    concurrency: usize,
+    checkpoint: Option<PathBuf>,
+}
+
+impl Default for ParallelDownload {
+    fn default() -> Self {
+        ParallelDownload {
+            part_size: Self::DEFAULT_PART_SIZE,
+            concurrency: Self::DEFAULT_CONCURRENCY,
+            checkpoint: None,
+        }
+    }
+}
+
+impl ParallelDownload {
+    /// 默认分片大小 8MB
+    pub const DEFAULT_PART_SIZE: u64 = 8 * 1024 * 1024;
+    /// 默认并发数
+    pub const DEFAULT_CONCURRENCY: usize = 4;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 每个范围请求下载的字节数，最小为 1
+    pub fn part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size.max(1);
+        self
+    }
+
+    /// 同时进行的范围请求数量，最小为 1
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 设置检查点文件，下载中断后可以从该文件记录的进度继续
+    pub fn checkpoint<P: Into<PathBuf>>(mut self, file: P) -> Self {
+        self.checkpoint = Some(file.into());
+        self
+    }
+}
+
+/// 并发下载的检查点，记录已写入本地文件的分片序号
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DownloadCheckpoint {
+    bucket: String,
+    path: String,
+    etag: String,
+    size: u64,
+    part_size: u64,
+    completed: Vec<u64>,
+}
+
+impl DownloadCheckpoint {
+    fn is_same_download(&self, other: &DownloadCheckpoint) -> bool {
+        self.bucket == other.bucket
+            && self.path == other.path
+            && self.etag == other.etag
+            && self.size == other.size
+            && self.part_size == other.part_size
+    }
+
+    /// 已有完成的分片时，本地文件必须存在且大小一致
+    async fn target_exists(&self, file: &Path) -> bool {
+        self.completed.is_empty()
+            || tokio::fs::metadata(file)
+                .await
+                .map(|m| m.len() == self.size)
+                .unwrap_or(false)
+    }
+}
+
+impl Object {
+    /// # 并发分片下载到本地文件
+    ///
+    /// 先通过 [`Object::get_info`] 获取文件大小和 ETag，按分片大小发起并发的范围请求，
+    /// 每个分片写入预先分配好大小的本地文件的对应位置。
+    ///
+    /// 每个范围请求都携带 `If-Match`，下载过程中 OSS 上的文件被修改时返回 [`OssError::SourceChanged`]。
+    /// 返回的内容与请求的范围不一致时返回 [`OssError::UnexpectedRange`]，
+    /// 只有第一个分片收到完整的文件时会直接使用该内容，不再请求其他分片。
+    ///
+    /// 下载失败且没有设置检查点时会删除本地文件，避免留下看起来已经完成的文件
+    pub async fn parallel_download<P: AsRef<Path>>(
+        &self,
+        file: P,
+        options: &ParallelDownload,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let file = file.as_ref();
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        let info = self.get_info(client).await?;
+
+        let fresh = DownloadCheckpoint {
+            bucket: bucket.as_str().to_owned(),
+            path: self.path.clone(),
+            etag: info.etag().to_owned(),
+            size: info.size(),
+            part_size: options.part_size,
+            completed: Vec::new(),
+        };
+
+        let mut checkpoint = fresh.clone();
+        if let Some(cp_file) = &options.checkpoint {
+            if let Some(cp) = read_checkpoint::<DownloadCheckpoint>(cp_file).await? {
+                if cp.is_same_download(&fresh) && cp.target_exists(file).await {
+                    checkpoint = cp;
+                }
+            }
+        }
+
+        let target = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(checkpoint.completed.is_empty())
+            .open(file)
+            .await?;
+        target.set_len(checkpoint.size).await?;
+        drop(target);
+
+        let part_count = checkpoint.size.div_ceil(checkpoint.part_size);
+        let pending: Vec<u64> = (0..part_count)
+            .filter(|i| !checkpoint.completed.contains(i))
+            .collect();
+
+        let etag = checkpoint.etag.clone();
+        let size = checkpoint.size;
+        let part_size = checkpoint.part_size;
+
+        let mut parts = stream::iter(pending)
+            .map(|index| {
+                let etag = etag.clone();
+                async move {
+                    let start = index * part_size;
+                    let end = (start + part_size).min(size) - 1;
+                    let options = DownloadOptions::new()
+                        .range(ByteRange::Bounded(start, end))
+                        .if_match(etag);
+
+                    // 第二个值表示是否已经得到了完整的文件
+                    let (content, whole) = match self.download_with(&options, client).await? {
+                        DownloadResponse::Partial { content, range, .. }
+                            if range.start() == start
+                                && range.end() == end
+                                && content.len() as u64 == end - start + 1 =>
+                        {
+                            (content, false)
+                        }
+                        // 服务端忽略了 Range，只有第一个分片可以直接写入完整的文件
+                        DownloadResponse::Full { content, .. }
+                            if index == 0 && content.len() as u64 == size =>
+                        {
+                            (content, true)
+                        }
+                        DownloadResponse::Partial { .. } | DownloadResponse::Full { .. } => {
+                            return Err(OssError::UnexpectedRange)
+                        }
+                        _ => return Err(OssError::SourceChanged),
+                    };
+
+                    let mut target = tokio::fs::OpenOptions::new().write(true).open(file).await?;
+                    target.seek(SeekFrom::Start(start)).await?;
+                    target.write_all(&content).await?;
+                    target.flush().await?;
+
+                    Ok::<(u64, bool), OssError>((index, whole))
+                }
+            })
+            .buffer_unordered(options.concurrency);
+
+        let res = async {
+            while let Some((index, whole)) = parts.try_next().await? {
+                if whole {
+                    // 其他分片的内容已经写入，不再继续请求
+                    break;
+                }
+                checkpoint.completed.push(index);
+                if let Some(cp_file) = &options.checkpoint {
+                    write_checkpoint(&checkpoint, cp_file).await?;
+                }
+            }
+            Ok(())
+        }
+        .await;
+        // 取消还在进行中的分片，之后才能安全地删除文件
+        drop(parts);
+
+        if let Err(e) = res {
+            match &options.checkpoint {
+                // 文件已被修改，检查点中的进度和已下载的内容都不再有效
+                Some(cp_file) if matches!(e, OssError::SourceChanged) => {
+                    let _ = tokio::fs::remove_file(cp_file).await;
+                    let _ = tokio::fs::remove_file(file).await;
+                }
+                // 保留本地文件和检查点，下次可以继续下载
+                Some(_) => (),
+                None => {
+                    let _ = tokio::fs::remove_file(file).await;
+                }
+            }
+            return Err(e);
+        }
+
+        if let Some(cp_file) = &options.checkpoint {
+            match tokio::fs::remove_file(cp_file).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{DownloadCheckpoint, ParallelDownload};
+    use crate::{
+        error::OssError,
+        object::resumable::write_checkpoint,
+        test_server::{self, serve_raw},
+        Object,
+    };
+
+    const CONTENT: &[u8] = b"0123456789";
+
+    fn meta() -> String {
+        "HTTP/1.1 200 OK\r\ncontent-length: 10\r\netag: \"A\"\r\n\
+        last-modified: Fri, 24 Feb 2012 06:07:48 GMT\r\nconnection: close\r\n\r\n"
+            .to_owned()
+    }
+
+    fn partial(start: usize, end: usize) -> String {
+        format!(
+            "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes {}-{}/10\r\n\
+            content-length: {}\r\nconnection: close\r\n\r\n{}",
+            start,
+            end,
+            end - start + 1,
+            String::from_utf8_lossy(&CONTENT[start..=end])
+        )
+    }
+
+    fn status(status: u16, body: &[u8]) -> String {
+        format!(
+            "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            String::from_utf8_lossy(body)
+        )
+    }
+
+    fn target(name: &str) -> (PathBuf, PathBuf) {
+        let file = std::env::temp_dir().join(format!("aliyun-oss-parallel-{}", name));
+        (file.clone(), file.with_extension("cp"))
+    }
+
+    fn checkpoint(completed: Vec<u64>) -> DownloadCheckpoint {
+        DownloadCheckpoint {
+            bucket: "bucket".to_owned(),
+            path: "big.zip".to_owned(),
+            etag: "\"A\"".to_owned(),
+            size: 10,
+            part_size: 4,
+            completed,
+        }
+    }
+
+    #[test]
+    fn test_same_download() {
+        let cp = DownloadCheckpoint {
+            bucket: "foo".to_owned(),
+            path: "big.zip".to_owned(),
+            etag: "\"A\"".to_owned(),
+            size: 1024,
+            part_size: 256,
+            completed: vec![0, 2],
+        };
+        let mut fresh = DownloadCheckpoint {
+            completed: vec![],
+            ..cp.clone()
+        };
+        assert!(cp.is_same_download(&fresh));
+
+        fresh.etag = "\"B\"".to_owned();
+        assert!(!cp.is_same_download(&fresh));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_download() {
+        let (file, _) = target("ranges");
+        let (server, handle) = serve_raw(vec![meta(), partial(0, 3), partial(4, 7), partial(8, 9)]);
+
+        let options = ParallelDownload::new().part_size(4).concurrency(1);
+        Object::new("big.zip")
+            .parallel_download(&file, &options, &test_server::client(&server))
+            .await
+            .unwrap();
+        assert_eq!(tokio::fs::read(&file).await.unwrap(), CONTENT);
+        tokio::fs::remove_file(&file).await.unwrap();
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "GET /big.zip?objectMeta HTTP/1.1");
+        let ranges: Vec<_> = requests[1..].iter().map(|r| r.header("range")).collect();
+        assert_eq!(
+            ranges,
+            [Some("bytes=0-3"), Some("bytes=4-7"), Some("bytes=8-9")]
+        );
+        assert!(requests[1..]
+            .iter()
+            .all(|r| r.header("if-match") == Some("\"A\"")));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_download_resume() {
+        let (file, cp_file) = target("resume");
+        // 第一个分片已经写入本地文件
+        let mut content = CONTENT[..4].to_vec();
+        content.resize(10, 0);
+        tokio::fs::write(&file, content).await.unwrap();
+        write_checkpoint(&checkpoint(vec![0]), &cp_file)
+            .await
+            .unwrap();
+
+        let (server, handle) = serve_raw(vec![meta(), partial(4, 7), partial(8, 9)]);
+        let options = ParallelDownload::new()
+            .part_size(4)
+            .concurrency(1)
+            .checkpoint(&cp_file);
+        Object::new("big.zip")
+            .parallel_download(&file, &options, &test_server::client(&server))
+            .await
+            .unwrap();
+        assert_eq!(tokio::fs::read(&file).await.unwrap(), CONTENT);
+        tokio::fs::remove_file(&file).await.unwrap();
+        assert!(!tokio::fs::try_exists(&cp_file).await.unwrap());
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].header("range"), Some("bytes=4-7"));
+        assert_eq!(requests[2].header("range"), Some("bytes=8-9"));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_download_source_changed() {
+        let (file, cp_file) = target("changed");
+        let (server, _handle) = serve_raw(vec![meta(), partial(0, 3), status(412, b"")]);
+
+        let options = ParallelDownload::new()
+            .part_size(4)
+            .concurrency(1)
+            .checkpoint(&cp_file);
+        let err = Object::new("big.zip")
+            .parallel_download(&file, &options, &test_server::client(&server))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, OssError::SourceChanged));
+        assert!(!tokio::fs::try_exists(&cp_file).await.unwrap());
+        assert!(!tokio::fs::try_exists(&file).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_parallel_download_full_response() {
+        let (file, _) = target("full");
+        // 第一个分片收到完整的文件，不再请求其他分片
+        let (server, handle) = serve_raw(vec![meta(), status(200, CONTENT)]);
+
+        let options = ParallelDownload::new().part_size(4).concurrency(1);
+        Object::new("big.zip")
+            .parallel_download(&file, &options, &test_server::client(&server))
+            .await
+            .unwrap();
+        assert_eq!(tokio::fs::read(&file).await.unwrap(), CONTENT);
+        tokio::fs::remove_file(&file).await.unwrap();
+
+        assert_eq!(handle.join().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_download_unexpected_range() {
+        let (file, _) = target("unexpected");
+        let options = ParallelDownload::new().part_size(4).concurrency(1);
+
+        // 第二个分片收到了完整的文件
+        let (server, _handle) = serve_raw(vec![meta(), partial(0, 3), status(200, CONTENT)]);
+        let err = Object::new("big.zip")
+            .parallel_download(&file, &options, &test_server::client(&server))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OssError::UnexpectedRange));
+        // 没有检查点时不会留下不完整的文件
+        assert!(!tokio::fs::try_exists(&file).await.unwrap());
+
+        // 206 的内容比请求的范围短
+        let (server, _handle) = serve_raw(vec![
+            meta(),
+            "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes 0-3/10\r\n\
+            content-length: 2\r\nconnection: close\r\n\r\n01"
+                .to_owned(),
+        ]);
+        let err = Object::new("big.zip")
+            .parallel_download(&file, &options, &test_server::client(&server))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OssError::UnexpectedRange));
+        assert!(!tokio::fs::try_exists(&file).await.unwrap());
+    }
+}
@@ -0,0 +1,368 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE},
+    Method,
+};
+use url::Url;
+
+use crate::{
+    client::Client,
+    error::OssError,
+    types::{append_query, CanonicalizedResource},
+    Object,
+};
+
+/// # 预签名 URL 的参数
+///
+/// 默认为 1 小时内有效的 GET 请求
+///
+/// ```
+/// # use std::time::Duration;
+/// # use aliyun_oss_client::object::PresignOptions;
+/// let options = PresignOptions::get()
+///     .expires(Duration::from_secs(600))
+///     .response_content_disposition("attachment; filename=\"report.pdf\"");
+///
+/// let upload = PresignOptions::put().content_type("image/png");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PresignOptions {
+    method: Method,
+    expires: Duration,
+    content_type: Option<String>,
+    response: Vec<(&'static str, String)>,
+}
+
+impl Default for PresignOptions {
+    fn default() -> Self {
+        PresignOptions {
+            method: Method::GET,
+            expires: Self::DEFAULT_EXPIRES,
+            content_type: None,
+            response: Vec::new(),
+        }
+    }
+}
+
+impl PresignOptions {
+    pub const DEFAULT_EXPIRES: Duration = Duration::from_secs(3600);
+
+    /// 下载文件
+    pub fn get() -> Self {
+        Self::default()
+    }
+
+    /// 上传文件，例如浏览器直传
+    pub fn put() -> Self {
+        Self::with_method(Method::PUT)
+    }
+
+    /// 获取文件的元信息
+    pub fn head() -> Self {
+        Self::with_method(Method::HEAD)
+    }
+
+    fn with_method(method: Method) -> Self {
+        PresignOptions {
+            method,
+            ..Self::default()
+        }
+    }
+
+    /// 有效时长，默认为 1 小时，V4 签名最长为 7 天
+    pub fn expires(mut self, expires: Duration) -> Self {
+        self.expires = expires;
+        self
+    }
+
+    /// 上传时的 `Content-Type`，会参与签名，使用 URL 上传时必须发送相同的值
+    pub fn content_type<T: Into<String>>(mut self, content_type: T) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// 覆盖响应中的 `Content-Type`
+    pub fn response_content_type<T: Into<String>>(self, value: T) -> Self {
+        self.response("response-content-type", value.into())
+    }
+
+    /// 覆盖响应中的 `Content-Disposition`，例如让浏览器以指定的文件名下载
+    pub fn response_content_disposition<T: Into<String>>(self, value: T) -> Self {
+        self.response("response-content-disposition", value.into())
+    }
+
+    /// 覆盖响应中的 `Cache-Control`
+    pub fn response_cache_control<T: Into<String>>(self, value: T) -> Self {
+        self.response("response-cache-control", value.into())
+    }
+
+    /// 覆盖响应中的 `Content-Language`
+    pub fn response_content_language<T: Into<String>>(self, value: T) -> Self {
+        self.response("response-content-language", value.into())
+    }
+
+    /// 覆盖响应中的 `Content-Encoding`
+    pub fn response_content_encoding<T: Into<String>>(self, value: T) -> Self {
+        self.response("response-content-encoding", value.into())
+    }
+
+    /// 覆盖响应中的 `Expires`
+    pub fn response_expires<T: Into<String>>(self, value: T) -> Self {
+        self.response("response-expires", value.into())
+    }
+
+    fn response(mut self, name: &'static str, value: String) -> Self {
+        self.response.retain(|(n, _)| *n != name);
+        self.response.push((name, value));
+        self
+    }
+
+    fn to_headers(&self) -> Result<HeaderMap, OssError> {
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = &self.content_type {
+            headers.insert(CONTENT_TYPE, content_type.try_into()?);
+        }
+        Ok(headers)
+    }
+}
+
+impl Object {
+    /// # 生成预签名 URL
+    ///
+    /// 不需要凭证就可以在有效期内访问该文件，签名方式与 client 的 [`SignVersion`](crate::client::SignVersion) 相同
+    ///
+    /// V4 签名的有效期超过 7 天时返回 [`OssError::InvalidConfig`]
+    ///
+    /// ```no_run
+    /// # use aliyun_oss_client::{Client, Object};
+    /// # use aliyun_oss_client::object::PresignOptions;
+    /// # async fn run(client: &Client) -> Result<(), aliyun_oss_client::error::OssError> {
+    /// let url = Object::new("report.pdf")
+    ///     .presign(&PresignOptions::get(), client)
+    ///     .await?;
+    /// println!("{}", url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn presign(
+        &self,
+        options: &PresignOptions,
+        client: &Client,
+    ) -> Result<Url, OssError> {
+        self.presign_at(options, Utc::now(), client).await
+    }
+
+    async fn presign_at(
+        &self,
+        options: &PresignOptions,
+        now: DateTime<Utc>,
+        client: &Client,
+    ) -> Result<Url, OssError> {
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+        let mut url = self.to_url(bucket);
+        for (name, value) in options.response.iter() {
+            append_query(&mut url, name, value);
+        }
+        let resource = CanonicalizedResource::new(format!("/{}/{}", bucket.as_str(), self.path));
+
+        client
+            .presign(
+                options.method.clone(),
+                url,
+                resource,
+                options.to_headers()?,
+                options.expires,
+                now,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use chrono::{TimeZone, Utc};
+    use reqwest::{header::HeaderMap, Method};
+    use sha2::{Digest, Sha256};
+    use url::Url;
+
+    use super::PresignOptions;
+    use crate::{
+        client::{Client, SignVersion},
+        credentials::Credentials,
+        error::OssError,
+        types::{hex, CanonicalizedResource, EndPoint, Key, Secret},
+        Bucket, Object,
+    };
+
+    fn client(version: SignVersion) -> Client {
+        let credentials = Credentials::new(Key::new("STS.foo"), Secret::new("bar"))
+            .with_security_token("CAIS-token");
+        let mut client = Client::from_credentials(credentials);
+        client.set_bucket(Bucket::new("bucket", EndPoint::CN_QINGDAO));
+        client.set_sign_version(version);
+        client
+    }
+
+    fn query(url: &Url) -> HashMap<String, String> {
+        url.query_pairs().into_owned().collect()
+    }
+
+    #[tokio::test]
+    async fn test_presign_v1() {
+        let options = PresignOptions::get()
+            .expires(Duration::from_secs(600))
+            .response_content_disposition("attachment; filename=\"a b.txt\"");
+        let url = Object::new("dir/a.txt")
+            .presign(&options, &client(SignVersion::V1))
+            .await
+            .unwrap();
+
+        assert!(url
+            .as_str()
+            .starts_with("https://bucket.oss-cn-qingdao.aliyuncs.com/dir/a.txt?response-content-disposition=attachment%3B%20filename%3D%22a%20b.txt%22&security-token=CAIS-token&OSSAccessKeyId=STS.foo&Expires="));
+
+        let query = query(&url);
+        let expires: i64 = query["Expires"].parse().unwrap();
+        assert!((expires - Utc::now().timestamp() - 600).abs() <= 1);
+
+        let string = format!(
+            "GET\n\n\n{}\n/bucket/dir/a.txt?response-content-disposition=attachment; filename=\"a b.txt\"&security-token=CAIS-token",
+            expires
+        );
+        assert_eq!(
+            query["Signature"],
+            Secret::new("bar").encryption(string.as_bytes()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_presign_put_content_type() {
+        let options = PresignOptions::put().content_type("image/png");
+        let url = Object::new("a.png")
+            .presign(&options, &client(SignVersion::V1))
+            .await
+            .unwrap();
+
+        let query = query(&url);
+        let string = format!(
+            "PUT\n\nimage/png\n{}\n/bucket/a.png?security-token=CAIS-token",
+            query["Expires"]
+        );
+        assert_eq!(
+            query["Signature"],
+            Secret::new("bar").encryption(string.as_bytes()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_presign_v4() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let options = PresignOptions::head().expires(Duration::from_secs(60));
+        let url = Object::new("a.txt")
+            .presign_at(&options, now, &client(SignVersion::V4))
+            .await
+            .unwrap();
+
+        let query = query(&url);
+        assert_eq!(query["x-oss-signature-version"], "OSS4-HMAC-SHA256");
+        assert_eq!(query["x-oss-security-token"], "CAIS-token");
+        assert_eq!(
+            query["x-oss-credential"],
+            "STS.foo/20240102/cn-qingdao/oss/aliyun_v4_request"
+        );
+        assert_eq!(query["x-oss-date"], "20240102T030405Z");
+        assert_eq!(query["x-oss-expires"], "60");
+
+        let canonical_request = "HEAD\n\
+            /bucket/a.txt\n\
+            x-oss-credential=STS.foo%2F20240102%2Fcn-qingdao%2Foss%2Faliyun_v4_request&\
+            x-oss-date=20240102T030405Z&\
+            x-oss-expires=60&\
+            x-oss-security-token=CAIS-token&\
+            x-oss-signature-version=OSS4-HMAC-SHA256\n\
+            \n\
+            \n\
+            UNSIGNED-PAYLOAD";
+        let string_to_sign = format!(
+            "OSS4-HMAC-SHA256\n20240102T030405Z\n20240102/cn-qingdao/oss/aliyun_v4_request\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signature = Secret::new("bar")
+            .encryption_v4("20240102", "cn-qingdao", string_to_sign.as_bytes())
+            .unwrap();
+        assert_eq!(query["x-oss-signature"], signature);
+        assert!(url
+            .as_str()
+            .ends_with(&format!("&x-oss-signature={}", signature)));
+    }
+
+    /// 阿里云 OSS Go SDK V2（alibabacloud-oss-go-sdk-v2）V4 预签名单元测试中的用例，
+    /// 签名值由官方 SDK 计算，不依赖本 crate 的实现
+    #[tokio::test]
+    async fn test_presign_v4_sdk_vector() {
+        let mut client = Client::new(Key::new("ak"), Secret::new("sk"));
+        client.set_sign_version(SignVersion::V4);
+
+        let url = Url::parse(
+            "http://bucket.oss-cn-hangzhou.aliyuncs.com/1234%2B-/123/1.txt\
+             ?param1=value1&%2Bparam1=value3&%7Cparam1=value4&%2Bparam2=&%7Cparam2=&param2=",
+        )
+        .unwrap();
+        let resource = CanonicalizedResource::new("/bucket/1234+-/123/1.txt".to_owned());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-oss-head1", "value".parse().unwrap());
+        headers.insert("abc", "value".parse().unwrap());
+        headers.insert("zabc", "value".parse().unwrap());
+        headers.insert("xyz", "value".parse().unwrap());
+        headers.insert("content-type", "application/octet-stream".parse().unwrap());
+        // 2023-12-17T02:54:37Z
+        let now = Utc.timestamp_opt(1702781677, 0).unwrap();
+
+        let url = client
+            .presign(
+                Method::PUT,
+                url,
+                resource,
+                headers,
+                Duration::from_secs(599),
+                now,
+            )
+            .await
+            .unwrap();
+
+        let query = query(&url);
+        assert_eq!(
+            query["x-oss-credential"],
+            "ak/20231217/cn-hangzhou/oss/aliyun_v4_request"
+        );
+        assert_eq!(query["x-oss-expires"], "599");
+        assert_eq!(
+            query["x-oss-signature"],
+            "a39966c61718be0d5b14e668088b3fa07601033f6518ac7b523100014269c0fe"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_presign_v4_expires_limit() {
+        let v4 = client(SignVersion::V4);
+        let options = PresignOptions::get().expires(Duration::from_secs(7 * 24 * 3600));
+        assert!(Object::new("a.txt").presign(&options, &v4).await.is_ok());
+
+        let options = options.expires(Duration::from_secs(7 * 24 * 3600 + 1));
+        let err = Object::new("a.txt")
+            .presign(&options, &v4)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OssError::InvalidConfig(_)));
+
+        // V1 签名没有该限制
+        assert!(Object::new("a.txt")
+            .presign(&options, &client(SignVersion::V1))
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,484 @@
+use std::{
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::{
+    client::Client,
+    error::{OssError, ServiceErrorCode},
+    object::multipart::{MultipartUpload, UploadedPart, MAX_PART_NUMBER, MIN_PART_SIZE},
+    Object,
+};
+
+/// # 断点续传的检查点
+///
+/// 以 json 格式保存在本地，记录上传事件 id、已完成的分片以及源文件的大小和修改时间，
+/// 进程重启后可以据此继续上传
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadCheckpoint {
+    bucket: String,
+    path: String,
+    upload_id: String,
+    part_size: u64,
+    source: SourceStat,
+    parts: Vec<UploadedPart>,
+}
+
+/// 源文件的状态，用于判断文件在两次上传之间是否被修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct SourceStat {
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+impl SourceStat {
+    async fn from_file(file: &Path) -> Result<SourceStat, OssError> {
+        let meta = tokio::fs::metadata(file).await?;
+        let modified = meta
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Ok(SourceStat {
+            size: meta.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+pub(super) async fn read_checkpoint<T: DeserializeOwned>(
+    file: &Path,
+) -> Result<Option<T>, OssError> {
+    match tokio::fs::read(file).await {
+        Ok(content) => Ok(Some(serde_json::from_slice(&content)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 先写入临时文件再重命名，避免进程中断时留下不完整的检查点
+///
+/// 临时文件名是在检查点文件名后追加 `.tmp`（`a.json` 对应 `a.json.tmp`），
+/// 不会替换原有的扩展名，避免扩展名不同的检查点共用同一个临时文件
+pub(super) async fn write_checkpoint<T: Serialize>(value: &T, file: &Path) -> Result<(), OssError> {
+    let mut tmp = file.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    tokio::fs::write(&tmp, serde_json::to_vec(value)?).await?;
+    tokio::fs::rename(&tmp, file).await?;
+    Ok(())
+}
+
+impl UploadCheckpoint {
+    fn new(upload: &MultipartUpload, part_size: u64, source: SourceStat) -> Self {
+        UploadCheckpoint {
+            bucket: upload.bucket().as_str().to_owned(),
+            path: upload.path().to_owned(),
+            upload_id: upload.upload_id().to_owned(),
+            part_size,
+            source,
+            parts: upload.parts().to_vec(),
+        }
+    }
+
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+
+    pub fn parts(&self) -> &[UploadedPart] {
+        &self.parts
+    }
+
+    /// 读取检查点文件，文件不存在时返回 `None`
+    pub async fn load<P: AsRef<Path>>(file: P) -> Result<Option<UploadCheckpoint>, OssError> {
+        read_checkpoint(file.as_ref()).await
+    }
+
+    /// 保存检查点
+    pub async fn save<P: AsRef<Path>>(&self, file: P) -> Result<(), OssError> {
+        write_checkpoint(self, file.as_ref()).await
+    }
+
+    fn is_same_upload(&self, upload: &Object, client: &Client, part_size: u64) -> bool {
+        let bucket = match client.bucket() {
+            Some(b) => b.as_str(),
+            None => return false,
+        };
+        self.bucket == bucket && self.path == upload.path && self.part_size == part_size
+    }
+}
+
+impl Object {
+    /// # 断点续传
+    ///
+    /// 将本地文件分片上传，并把进度写入 `checkpoint` 文件。
+    /// 如果检查点已存在且源文件未被修改，则跳过 OSS 已确认接收的分片继续上传；
+    /// 源文件有变化时会放弃旧的上传事件，重新开始。
+    /// 上传完成后会删除检查点文件
+    pub async fn resumable_upload<F: AsRef<Path>, C: AsRef<Path>>(
+        &self,
+        file: F,
+        checkpoint: C,
+        part_size: u64,
+        client: &Client,
+    ) -> Result<(), OssError> {
+        let file = file.as_ref();
+        let checkpoint = checkpoint.as_ref();
+        if part_size < MIN_PART_SIZE {
+            return Err(OssError::InvalidPartSize);
+        }
+        let bucket = client.bucket().ok_or(OssError::NoFoundBucket)?;
+
+        let source = SourceStat::from_file(file).await?;
+        let part_count = source.size.div_ceil(part_size).max(1);
+        if part_count > MAX_PART_NUMBER as u64 {
+            return Err(OssError::InvalidPartSize);
+        }
+
+        let mut upload = match UploadCheckpoint::load(checkpoint).await? {
+            Some(cp) if cp.is_same_upload(self, client, part_size) && cp.source == source => {
+                let upload = MultipartUpload::resume(
+                    bucket.clone(),
+                    self.path.clone(),
+                    cp.upload_id,
+                    vec![],
+                );
+                match upload.list_parts(client).await {
+                    Ok(parts) => MultipartUpload::resume(
+                        bucket.clone(),
+                        self.path.clone(),
+                        upload.upload_id(),
+                        parts,
+                    ),
+                    // 上传事件已失效（被取消或过期），重新开始
+                    Err(e) if e.service_code() == Some(&ServiceErrorCode::NoSuchUpload) => {
+                        self.initiate_multipart_upload(client).await?
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            Some(cp) => {
+                if cp.is_same_upload(self, client, cp.part_size) {
+                    let stale = MultipartUpload::resume(
+                        bucket.clone(),
+                        self.path.clone(),
+                        cp.upload_id,
+                        vec![],
+                    );
+                    // 旧的上传事件只是被放弃，清理失败不影响本次上传
+                    let _ = stale.abort(client).await;
+                }
+                self.initiate_multipart_upload(client).await?
+            }
+            None => self.initiate_multipart_upload(client).await?,
+        };
+        UploadCheckpoint::new(&upload, part_size, source)
+            .save(checkpoint)
+            .await?;
+
+        let mut reader = tokio::fs::File::open(file).await?;
+
+        for index in 0..part_count {
+            let part_number = index as u32 + 1;
+            if upload
+                .parts()
+                .iter()
+                .any(|p| p.part_number() == part_number)
+            {
+                continue;
+            }
+
+            reader.seek(SeekFrom::Start(index * part_size)).await?;
+            let mut content = Vec::new();
+            (&mut reader)
+                .take(part_size)
+                .read_to_end(&mut content)
+                .await?;
+
+            upload
+                .upload_part_with_number(part_number, content, client)
+                .await?;
+            UploadCheckpoint::new(&upload, part_size, source)
+                .save(checkpoint)
+                .await?;
+        }
+
+        if SourceStat::from_file(file).await? != source {
+            tokio::fs::remove_file(checkpoint).await?;
+            let _ = upload.abort(client).await;
+            return Err(OssError::SourceChanged);
+        }
+
+        upload.complete(client).await?;
+        tokio::fs::remove_file(checkpoint).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{write_checkpoint, SourceStat, UploadCheckpoint};
+    use crate::{
+        bucket::Bucket,
+        error::OssError,
+        object::multipart::{MultipartUpload, UploadedPart, MIN_PART_SIZE},
+        test_server::{self, serve_raw, serve_raw_with},
+        types::EndPoint,
+        Object,
+    };
+
+    #[tokio::test]
+    async fn test_checkpoint_save_load() {
+        let upload = MultipartUpload::resume(
+            Bucket::new("foo", EndPoint::CN_QINGDAO),
+            "big.zip",
+            "0004B9894A22E5B1888A1E29F823",
+            vec![UploadedPart::new(1, "\"A\""), UploadedPart::new(2, "\"B\"")],
+        );
+        let source = SourceStat {
+            size: 1024,
+            modified_secs: 1_700_000_000,
+            modified_nanos: 12,
+        };
+        let cp = UploadCheckpoint::new(&upload, 512, source);
+
+        let file = std::env::temp_dir().join("aliyun-oss-checkpoint-test.json");
+        cp.save(&file).await.unwrap();
+        let loaded = UploadCheckpoint::load(&file).await.unwrap();
+        tokio::fs::remove_file(&file).await.unwrap();
+
+        assert_eq!(loaded, Some(cp));
+        assert!(UploadCheckpoint::load(&file).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_write_checkpoint_tmp_name() {
+        let dir = std::env::temp_dir().join("aliyun-oss-checkpoint-tmp-test");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        // 扩展名不同的检查点不会共用临时文件，也不会覆盖已有的 `a.tmp`
+        tokio::fs::write(dir.join("a.tmp"), "user").await.unwrap();
+
+        for name in ["a.json", "a.cp"] {
+            write_checkpoint(&name, &dir.join(name)).await.unwrap();
+        }
+        let mut names = vec![];
+        let mut entries = tokio::fs::read_dir(&dir).await.unwrap();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name().into_string().unwrap());
+        }
+        names.sort();
+        let user = tokio::fs::read_to_string(dir.join("a.tmp")).await.unwrap();
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+        assert_eq!(names, ["a.cp", "a.json", "a.tmp"]);
+        assert_eq!(user, "user");
+    }
+
+    fn response(status: u16, etag: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {} X\r\netag: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            etag,
+            body.len(),
+            body
+        )
+    }
+
+    fn initiate(upload_id: &str) -> String {
+        response(
+            200,
+            "",
+            &format!(
+                "<InitiateMultipartUploadResult><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                upload_id
+            ),
+        )
+    }
+
+    /// 写入两个分片大小的源文件，返回源文件和检查点的路径
+    async fn source_file(name: &str) -> (PathBuf, PathBuf) {
+        let file = std::env::temp_dir().join(format!("aliyun-oss-resumable-{}", name));
+        let content: Vec<u8> = (0..MIN_PART_SIZE + 10).map(|i| i as u8).collect();
+        tokio::fs::write(&file, content).await.unwrap();
+        (file.clone(), file.with_extension("cp"))
+    }
+
+    /// 按照源文件当前的状态写入检查点
+    async fn save_checkpoint(file: &Path, checkpoint: &Path, upload_id: &str) {
+        let upload = MultipartUpload::resume(
+            Bucket::new("bucket", EndPoint::CN_QINGDAO),
+            "big.zip",
+            upload_id,
+            vec![],
+        );
+        let source = SourceStat::from_file(file).await.unwrap();
+        UploadCheckpoint::new(&upload, MIN_PART_SIZE, source)
+            .save(checkpoint)
+            .await
+            .unwrap();
+    }
+
+    async fn cleanup(file: &Path, checkpoint: &Path) -> bool {
+        tokio::fs::remove_file(file).await.unwrap();
+        tokio::fs::try_exists(checkpoint).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_skip_parts() {
+        let (file, checkpoint) = source_file("skip").await;
+        save_checkpoint(&file, &checkpoint, "upload-1").await;
+
+        let (server, handle) = serve_raw(vec![
+            response(
+                200,
+                "",
+                "<ListPartsResult><IsTruncated>false</IsTruncated>\
+                <Part><PartNumber>1</PartNumber><ETag>\"e1\"</ETag></Part></ListPartsResult>",
+            ),
+            response(200, "\"e2\"", ""),
+            response(200, "", ""),
+        ]);
+        Object::new("big.zip")
+            .resumable_upload(
+                &file,
+                &checkpoint,
+                MIN_PART_SIZE,
+                &test_server::client(&server),
+            )
+            .await
+            .unwrap();
+        assert!(!cleanup(&file, &checkpoint).await);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "GET /big.zip?uploadId=upload-1 HTTP/1.1");
+        assert_eq!(
+            requests[1].line,
+            "PUT /big.zip?partNumber=2&uploadId=upload-1 HTTP/1.1"
+        );
+        assert_eq!(requests[1].body.len(), 10);
+        assert_eq!(requests[2].line, "POST /big.zip?uploadId=upload-1 HTTP/1.1");
+        let body = String::from_utf8_lossy(&requests[2].body);
+        assert!(body.contains("<PartNumber>1</PartNumber><ETag>\"e1\"</ETag>"));
+        assert!(body.contains("<PartNumber>2</PartNumber><ETag>\"e2\"</ETag>"));
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_no_such_upload() {
+        let (file, checkpoint) = source_file("no-such-upload").await;
+        save_checkpoint(&file, &checkpoint, "upload-1").await;
+
+        let (server, handle) = serve_raw(vec![
+            response(404, "", "<Error><Code>NoSuchUpload</Code></Error>"),
+            initiate("upload-2"),
+            response(200, "\"e1\"", ""),
+            response(200, "\"e2\"", ""),
+            response(200, "", ""),
+        ]);
+        Object::new("big.zip")
+            .resumable_upload(
+                &file,
+                &checkpoint,
+                MIN_PART_SIZE,
+                &test_server::client(&server),
+            )
+            .await
+            .unwrap();
+        assert!(!cleanup(&file, &checkpoint).await);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].line, "GET /big.zip?uploadId=upload-1 HTTP/1.1");
+        assert_eq!(requests[1].line, "POST /big.zip?uploads HTTP/1.1");
+        assert_eq!(
+            requests[2].line,
+            "PUT /big.zip?partNumber=1&uploadId=upload-2 HTTP/1.1"
+        );
+        assert_eq!(requests[4].line, "POST /big.zip?uploadId=upload-2 HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_stale_checkpoint() {
+        let (file, checkpoint) = source_file("stale").await;
+        save_checkpoint(&file, &checkpoint, "upload-1").await;
+        // 检查点保存之后源文件被修改
+        tokio::fs::write(&file, vec![0; MIN_PART_SIZE as usize + 20])
+            .await
+            .unwrap();
+
+        let (server, handle) = serve_raw(vec![
+            // 取消旧的上传事件失败不影响本次上传
+            response(404, "", "<Error><Code>NoSuchUpload</Code></Error>"),
+            initiate("upload-2"),
+            response(200, "\"e1\"", ""),
+            response(200, "\"e2\"", ""),
+            response(200, "", ""),
+        ]);
+        Object::new("big.zip")
+            .resumable_upload(
+                &file,
+                &checkpoint,
+                MIN_PART_SIZE,
+                &test_server::client(&server),
+            )
+            .await
+            .unwrap();
+        assert!(!cleanup(&file, &checkpoint).await);
+
+        let requests = handle.join().unwrap();
+        assert_eq!(
+            requests[0].line,
+            "DELETE /big.zip?uploadId=upload-1 HTTP/1.1"
+        );
+        assert_eq!(requests[1].line, "POST /big.zip?uploads HTTP/1.1");
+        assert_eq!(requests[3].body.len(), 20);
+        assert_eq!(requests[4].line, "POST /big.zip?uploadId=upload-2 HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_source_changed() {
+        let (file, checkpoint) = source_file("changed").await;
+
+        // 上传第二个分片时源文件被修改
+        let changed = file.clone();
+        let (server, handle) = serve_raw_with(
+            vec![
+                initiate("upload-1"),
+                response(200, "\"e1\"", ""),
+                response(200, "\"e2\"", ""),
+                response(204, "", ""),
+            ],
+            move |request| {
+                if request.line.starts_with("PUT /big.zip?partNumber=2") {
+                    std::fs::write(&changed, "changed").unwrap();
+                }
+            },
+        );
+        let err = Object::new("big.zip")
+            .resumable_upload(
+                &file,
+                &checkpoint,
+                MIN_PART_SIZE,
+                &test_server::client(&server),
+            )
+            .await
+            .unwrap_err();
+        assert!(!cleanup(&file, &checkpoint).await);
+
+        assert!(matches!(err, OssError::SourceChanged));
+        let requests = handle.join().unwrap();
+        assert_eq!(
+            requests[3].line,
+            "DELETE /big.zip?uploadId=upload-1 HTTP/1.1"
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+
+use crate::error::OssError;
+
+/// # 上传文件的参数
+///
+/// 没有设置 `Content-Type` 时不会发送该请求头，由 OSS 决定文件的类型
+///
+/// ```
+/// # use aliyun_oss_client::object::UploadOptions;
+/// let options = UploadOptions::new().content_type("image/png");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UploadOptions {
+    content_type: Option<String>,
+}
+
+impl UploadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 文件的 `Content-Type`，会参与签名
+    pub fn content_type<T: Into<String>>(mut self, content_type: T) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub(crate) fn to_headers(&self) -> Result<HeaderMap, OssError> {
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = &self.content_type {
+            headers.insert(CONTENT_TYPE, content_type.try_into()?);
+        }
+        Ok(headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UploadOptions;
+
+    #[test]
+    fn test_options_headers() {
+        assert!(UploadOptions::new().to_headers().unwrap().is_empty());
+
+        let headers = UploadOptions::new()
+            .content_type("text/plain")
+            .to_headers()
+            .unwrap();
+        assert_eq!(headers.get("content-type").unwrap(), "text/plain");
+    }
+}
@@ -0,0 +1,148 @@
+//! 测试用的本地 HTTP 服务，按顺序返回预设的响应，并记录收到的请求
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread::JoinHandle,
+};
+
+use crate::{
+    types::{EndPoint, Key, Secret},
+    Bucket, Client,
+};
+
+/// 服务收到的一个请求
+#[derive(Debug)]
+pub(crate) struct Request {
+    /// 请求行，例如 `GET /foo.txt HTTP/1.1`
+    pub line: String,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// 不区分大小写地查找请求头
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// 默认 bucket 为 `bucket`，请求都发往 `server` 的 client
+pub(crate) fn client(server: &str) -> Client {
+    let mut client = Client::new(Key::new("foo"), Secret::new("bar"));
+    client.set_bucket(Bucket::new("bucket", EndPoint::CN_QINGDAO));
+    client.set_test_server(server);
+    client
+}
+
+/// 依次返回给定状态码和响应体，结束后返回收到的全部请求
+pub(crate) fn serve(responses: Vec<(u16, String)>) -> (String, JoinHandle<Vec<Request>>) {
+    serve_raw(
+        responses
+            .into_iter()
+            .map(|(status, body)| {
+                format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+            })
+            .collect(),
+    )
+}
+
+/// 依次原样返回给定的响应报文，可以用来模拟被截断的响应
+pub(crate) fn serve_raw(responses: Vec<String>) -> (String, JoinHandle<Vec<Request>>) {
+    serve_raw_with(responses, |_| ())
+}
+
+/// 与 [`serve_raw`] 相同，每收到一个请求、返回响应之前先调用 `on_request`，
+/// 可以用来模拟请求过程中本地文件被修改
+pub(crate) fn serve_raw_with<F>(
+    responses: Vec<String>,
+    mut on_request: F,
+) -> (String, JoinHandle<Vec<Request>>)
+where
+    F: FnMut(&Request) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = std::thread::spawn(move || {
+        let mut requests = vec![];
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            on_request(&request);
+            requests.push(request);
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+        requests
+    });
+
+    (url, handle)
+}
+
+fn read_request(stream: &mut TcpStream) -> Request {
+    let mut buf = Vec::new();
+    let head_len = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if !read_more(stream, &mut buf) {
+            break buf.len();
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_len]).into_owned();
+    let mut lines = head.lines();
+    let line = lines.next().unwrap_or_default().to_owned();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(n, v)| (n.trim().to_owned(), v.trim().to_owned()))
+        .collect();
+
+    let mut request = Request {
+        line,
+        headers,
+        body: buf[head_len..].to_vec(),
+    };
+
+    if let Some(len) = request.header("content-length") {
+        let len: usize = len.parse().unwrap();
+        while request.body.len() < len && read_more(stream, &mut request.body) {}
+    } else if request.header("transfer-encoding") == Some("chunked") {
+        while !request.body.ends_with(b"0\r\n\r\n") && read_more(stream, &mut request.body) {}
+        request.body = decode_chunked(&request.body);
+    }
+    request
+}
+
+fn read_more(stream: &mut TcpStream, buf: &mut Vec<u8>) -> bool {
+    let mut chunk = [0; 4096];
+    match stream.read(&mut chunk) {
+        Ok(0) | Err(_) => false,
+        Ok(len) => {
+            buf.extend_from_slice(&chunk[..len]);
+            true
+        }
+    }
+}
+
+fn decode_chunked(mut data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    while let Some(pos) = data.windows(2).position(|w| w == b"\r\n") {
+        let size = usize::from_str_radix(&String::from_utf8_lossy(&data[..pos]), 16).unwrap_or(0);
+        if size == 0 {
+            break;
+        }
+        let start = pos + 2;
+        body.extend_from_slice(&data[start..start + size]);
+        data = &data[start + size + 2..];
+    }
+    body
+}
@@ -1,5 +1,9 @@
 use std::{collections::HashMap, env::VarError};
 
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use url::Url;
+
 use crate::bucket::Bucket;
 
 mod endpoint;
@@ -58,8 +62,147 @@ impl Secret {
 
         Ok(STANDARD.encode(sha1))
     }
+
+    /// # V4 签名
+    /// 由 secret、日期、地域逐级派生出签名密钥，再对 `string_to_sign` 签名，返回十六进制字符串
+    pub(crate) fn encryption_v4(
+        &self,
+        date: &str,
+        region: &str,
+        string_to_sign: &[u8],
+    ) -> Result<String, hmac::digest::crypto_common::InvalidLength> {
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+        type HmacSha256 = Hmac<Sha256>;
+
+        let hmac = |key: &[u8], data: &[u8]| {
+            let mut mac = HmacSha256::new_from_slice(key)?;
+            mac.update(data);
+            Ok(mac.finalize().into_bytes())
+        };
+
+        let key = format!("aliyun_v4{}", self.0);
+        let key = hmac(key.as_bytes(), date.as_bytes())?;
+        let key = hmac(&key, region.as_bytes())?;
+        let key = hmac(&key, b"oss")?;
+        let key = hmac(&key, b"aliyun_v4_request")?;
+
+        Ok(hex(&hmac(&key, string_to_sign)?))
+    }
+}
+
+/// 小写的十六进制编码
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 除 `-_.~` 以外的字符都会被编码
+const OSS_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// 对查询参数进行 URL 编码
+pub(crate) fn encode_query(value: &str) -> String {
+    utf8_percent_encode(value, OSS_ENCODE_SET).to_string()
+}
+
+/// 向 url 中追加一个查询参数，名称和值都按 [`encode_query`] 编码，值为空时只保留名称
+pub(crate) fn append_query(url: &mut Url, key: &str, value: &str) {
+    let pair = if value.is_empty() {
+        encode_query(key)
+    } else {
+        format!("{}={}", encode_query(key), encode_query(value))
+    };
+    let query = match url.query() {
+        Some(query) if !query.is_empty() => format!("{}&{}", query, pair),
+        _ => pair,
+    };
+    url.set_query(Some(&query));
 }
 
+/// 对 object 路径进行 URL 编码，保留路径中的 `/`
+/// ```
+/// # use aliyun_oss_client::types::encode_path;
+/// assert_eq!(encode_path("dir/中文 a+b.txt"), "dir/%E4%B8%AD%E6%96%87%20a%2Bb.txt");
+/// ```
+pub fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|p| utf8_percent_encode(p, OSS_ENCODE_SET).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// V1 签名时需要加入 `CanonicalizedResource` 的子资源，其他查询参数不参与签名
+const SUB_RESOURCES: &[&str] = &[
+    "acl",
+    "append",
+    "asyncFetch",
+    "bucketInfo",
+    "callback",
+    "callback-var",
+    "cname",
+    "comp",
+    "continuation-token",
+    "cors",
+    "delete",
+    "encryption",
+    "endTime",
+    "img",
+    "inventory",
+    "inventoryId",
+    "lifecycle",
+    "live",
+    "location",
+    "logging",
+    "metaQuery",
+    "objectMeta",
+    "partNumber",
+    "policy",
+    "position",
+    "qos",
+    "qosInfo",
+    "referer",
+    "regionList",
+    "replication",
+    "replicationLocation",
+    "replicationProgress",
+    "requestPayment",
+    "resourceGroup",
+    "response-cache-control",
+    "response-content-disposition",
+    "response-content-encoding",
+    "response-content-language",
+    "response-content-type",
+    "response-expires",
+    "restore",
+    "security-token",
+    "sequential",
+    "startTime",
+    "stat",
+    "status",
+    "style",
+    "styleName",
+    "symlink",
+    "tagging",
+    "transferAcceleration",
+    "uploadId",
+    "uploads",
+    "versionId",
+    "versioning",
+    "versions",
+    "vod",
+    "website",
+    "worm",
+    "wormExtend",
+    "wormId",
+    "x-oss-process",
+    "x-oss-request-payer",
+    "x-oss-traffic-limit",
+];
+
+#[derive(Debug, Clone)]
 pub struct CanonicalizedResource(String);
 
 impl Default for CanonicalizedResource {
@@ -76,6 +219,45 @@ impl CanonicalizedResource {
         &self.0
     }
 
+    /// 资源中 `?` 之前的部分，如 `/bucket/object`
+    pub fn path(&self) -> &str {
+        self.0.split_once('?').map_or(&self.0, |(path, _)| path)
+    }
+
+    /// # 按照实际发送的请求生成规范的资源
+    ///
+    /// 保留资源的路径，子资源取自 `url` 的查询参数：只保留需要签名的子资源，并按名称排序，
+    /// 没有值的子资源只保留名称
+    /// ```
+    /// # use aliyun_oss_client::types::CanonicalizedResource;
+    /// # use url::Url;
+    /// let url = Url::parse("https://b.oss-cn-qingdao.aliyuncs.com/a.txt?uploadId=1&partNumber=2&foo=3&acl").unwrap();
+    /// let resource = CanonicalizedResource::new("/b/a.txt".to_owned());
+    /// assert_eq!(
+    ///     resource.canonicalize(&url).as_str(),
+    ///     "/b/a.txt?acl&partNumber=2&uploadId=1"
+    /// );
+    /// ```
+    pub fn canonicalize(&self, url: &Url) -> CanonicalizedResource {
+        let mut list: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(k, _)| SUB_RESOURCES.contains(&k.as_ref()))
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        list.sort();
+
+        let mut string = self.path().to_owned();
+        for (i, (k, v)) in list.into_iter().enumerate() {
+            string += if i == 0 { "?" } else { "&" };
+            string += &k;
+            if !v.is_empty() {
+                string += "=";
+                string += &v;
+            }
+        }
+        Self(string)
+    }
+
     pub fn from_bucket_info(bucket: &Bucket) -> CanonicalizedResource {
         Self(format!("/{}/?bucketInfo", bucket.as_str()))
     }
@@ -182,7 +364,7 @@ impl ObjectQuery {
             query_str += "&";
             query_str += key;
             query_str += "=";
-            query_str += value;
+            query_str += &utf8_percent_encode(value, OSS_ENCODE_SET).to_string();
         }
         query_str
     }
@@ -190,4 +372,25 @@ impl ObjectQuery {
     pub fn insert_next_token(&mut self, token: String) -> Option<String> {
         self.map.insert(Self::CONTINUATION_TOKEN.into(), token)
     }
+
+    /// 列表结果中包含文件的拥有者信息
+    pub fn insert_fetch_owner(&mut self) -> Option<String> {
+        self.map.insert(Self::FETCH_OWNER.into(), "true".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObjectQuery;
+
+    #[test]
+    fn test_to_oss_query() {
+        let mut query = ObjectQuery::new();
+        query.insert_next_token("ChR0ZXN0/5paH5Lu2+LnR4dA==".to_owned());
+
+        assert_eq!(
+            query.to_oss_query(),
+            "list-type=2&continuation-token=ChR0ZXN0%2F5paH5Lu2%2BLnR4dA%3D%3D"
+        );
+    }
 }
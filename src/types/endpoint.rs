@@ -1,9 +1,6 @@
 use crate::error::OssError;
 
-use serde::{
-    de::{value, Visitor},
-    Deserialize,
-};
+use serde::{de::Visitor, Deserialize};
 use url::Url;
 
 /// # OSS 的可用区
@@ -276,6 +273,19 @@ impl EndPoint {
     /// );
     /// ```
     pub fn to_url(&self) -> Url {
+        self.try_to_url()
+            .unwrap_or_else(|_| panic!("covert to url failed, endpoint: {}", self.as_ref()))
+    }
+
+    /// 与 `to_url` 相同，无法转换时返回错误而不是 panic
+    /// ```
+    /// # use aliyun_oss_client::types::{EndPoint, EndPointKind};
+    /// assert!(EndPoint::CN_SHANGHAI.try_to_url().is_ok());
+    /// assert!(EndPoint::init(EndPointKind::Other("a b".to_owned()), false)
+    ///     .try_to_url()
+    ///     .is_err());
+    /// ```
+    pub fn try_to_url(&self) -> Result<Url, OssError> {
         const OSS_DOMAIN_PREFIX: &str = "https://oss-";
         let mut url = String::from(OSS_DOMAIN_PREFIX);
         url.push_str(self.as_ref());
@@ -286,7 +296,7 @@ impl EndPoint {
         }
 
         url.push_str(OSS_DOMAIN_MAIN);
-        Url::parse(&url).unwrap_or_else(|_| panic!("covert to url failed, endpoint: {}", url))
+        Url::parse(&url).map_err(|_| OssError::InvalidEndPoint)
     }
 }
 